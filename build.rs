@@ -1,3 +1,95 @@
+use std::{path::Path, process::Command};
+
+/// Schemas for the less common file types, which are built in from the schemastore checkout,
+/// along with the name of the constant each one is available as.
+const OPTIONAL_SCHEMAS: &[(&str, &str)] = &[
+    ("DEPENDABOT_SCHEMA", "dependabot-2.0.json"),
+    ("ISSUE_FORM_SCHEMA", "github-issue-forms.json"),
+    ("ISSUE_CONFIG_SCHEMA", "github-issue-config.json"),
+    ("FUNDING_SCHEMA", "github-funding.json"),
+    ("RELEASE_CONFIG_SCHEMA", "github-release-config.json"),
+    (
+        "WORKFLOW_TEMPLATE_SCHEMA",
+        "github-workflow-template-properties.json",
+    ),
+];
+
+const SCHEMA_DIR: &str = "src/schemastore/src/schemas/json";
+
+/// Set to build without any of the optional schemas that are missing, rather than failing.
+const ALLOW_MISSING: &str = "ACTION_VALIDATOR_ALLOW_MISSING_SCHEMAS";
+
 fn main() {
     println!("cargo:rerun-if-changed=tests/fixtures");
+    println!("cargo:rerun-if-changed={SCHEMA_DIR}");
+    println!("cargo:rerun-if-env-changed=SCHEMASTORE_COMMIT");
+    println!("cargo:rerun-if-env-changed={ALLOW_MISSING}");
+    println!("cargo:rustc-check-cfg=cfg(missing_schemas)");
+
+    println!(
+        "cargo:rustc-env=SCHEMASTORE_COMMIT={}",
+        schemastore_commit()
+    );
+
+    write_optional_schemas();
+}
+
+/// The commit the schemastore submodule is at.  Packaged builds aren't in a git repository,
+/// so it can be given with `SCHEMASTORE_COMMIT` instead.
+fn schemastore_commit() -> String {
+    if let Ok(commit) = std::env::var("SCHEMASTORE_COMMIT") {
+        return commit;
+    }
+
+    // A submodule is recorded in its parent's tree as a "commit" entry
+    Command::new("git")
+        .args(["ls-tree", "HEAD", "src/schemastore"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            let entry = String::from_utf8_lossy(&output.stdout).into_owned();
+            let mut fields = entry.split_whitespace();
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some("160000"), Some("commit"), Some(commit)) => Some(commit.to_string()),
+                _ => None,
+            }
+        })
+        .unwrap_or_else(|| "unknown".into())
+}
+
+fn write_optional_schemas() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+
+    let mut missing = vec![];
+    let consts: String = OPTIONAL_SCHEMAS
+        .iter()
+        .map(|(name, file)| {
+            let path = Path::new(&manifest_dir).join(SCHEMA_DIR).join(file);
+            if path.exists() {
+                format!("pub const {name}: Option<&[u8]> = Some(include_bytes!({path:?}));\n")
+            } else {
+                missing.push(*file);
+                format!("pub const {name}: Option<&[u8]> = None;\n")
+            }
+        })
+        .collect();
+
+    // Quietly leaving a schema out would mean files of that type silently go unchecked
+    if !missing.is_empty() {
+        if std::env::var_os(ALLOW_MISSING).is_none() {
+            panic!(
+                "{} missing from {SCHEMA_DIR}; run `git submodule update --init` to check out schemastore, or set {ALLOW_MISSING}=1 to build without them",
+                missing.join(", ")
+            );
+        }
+
+        for file in &missing {
+            println!("cargo:warning=Building without {file}, so files that need it won't be checked against a schema");
+        }
+        println!("cargo:rustc-cfg=missing_schemas");
+    }
+
+    std::fs::write(Path::new(&out_dir).join("optional_schemas.rs"), consts).unwrap();
 }
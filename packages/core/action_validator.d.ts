@@ -4,23 +4,36 @@ export type ParseErrorLocation = {
   column: number;
 };
 
+export type Severity = "error" | "warning" | "info";
+
 export type ValidationError =
   | {
       code: string;
+      severity: Severity;
       detail?: string;
       path: string;
       title: string;
       states?: Omit<ValidationState, "actionType">[];
+      steps?: string[];
     }
   | {
       code: string;
+      severity: Severity;
       detail: string;
       title: string;
       location?: ParseErrorLocation;
     };
 
 export type ValidationState = {
-  actionType: "action" | "workflow";
+  actionType:
+    | "action"
+    | "workflow"
+    | "dependabot"
+    | "issue-form"
+    | "issue-config"
+    | "funding"
+    | "release-config"
+    | "workflow-template";
   errors: ValidationError[];
 };
 
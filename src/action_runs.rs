@@ -0,0 +1,128 @@
+use serde_json::Value;
+use std::path::Path;
+
+use crate::{
+    schemas, system,
+    uses::Reference,
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// Node runtimes which GitHub no longer runs actions with.
+const UNSUPPORTED_NODE_RUNTIMES: &[&str] = &["node12", "node16"];
+
+/// Node runtimes which still work, but are on their way out.
+const DEPRECATED_NODE_RUNTIMES: &[&str] = &["node20"];
+
+const CURRENT_NODE_RUNTIME: &str = "node24";
+
+/// Check what an action runs.  Files the action refers to can only be checked when we know
+/// which directory the action is in.
+pub fn validate_runs(doc: &Value, action_dir: Option<&Path>, state: &mut ValidationState) {
+    let runs = &doc["runs"];
+    let Some(using) = runs["using"].as_str() else {
+        return;
+    };
+
+    match using {
+        "composite" => validate_composite(runs, state),
+        "docker" => validate_docker(runs, action_dir, state),
+        node => validate_node(node, runs, action_dir, state),
+    }
+}
+
+fn validate_node(
+    using: &str,
+    runs: &Value,
+    action_dir: Option<&Path>,
+    state: &mut ValidationState,
+) {
+    if UNSUPPORTED_NODE_RUNTIMES.contains(&using) {
+        state.errors.push(ValidationError::ActionRuns {
+            code: "unsupported_node_runtime".into(),
+            severity: Severity::Error,
+            path: "/runs/using".into(),
+            title: "Unsupported Node runtime".into(),
+            detail: Some(format!(
+                "GitHub no longer supports running actions with {using}; update the action to run with {CURRENT_NODE_RUNTIME}"
+            )),
+        });
+    } else if DEPRECATED_NODE_RUNTIMES.contains(&using) {
+        state.errors.push(ValidationError::ActionRuns {
+            code: "deprecated_node_runtime".into(),
+            severity: Severity::Warning,
+            path: "/runs/using".into(),
+            title: "Deprecated Node runtime".into(),
+            detail: Some(format!(
+                "{using} is deprecated, and will be removed by GitHub; update the action to run with {CURRENT_NODE_RUNTIME}"
+            )),
+        });
+    }
+
+    let Some(action_dir) = action_dir else {
+        return;
+    };
+
+    for key in ["main", "pre", "post"] {
+        if let Some(file) = runs[key].as_str() {
+            validate_file_exists(file, action_dir, &format!("/runs/{key}"), state);
+        }
+    }
+}
+
+fn validate_docker(runs: &Value, action_dir: Option<&Path>, state: &mut ValidationState) {
+    let Some(image) = runs["image"].as_str() else {
+        return;
+    };
+
+    if image.starts_with("docker://") {
+        if let Err(problem) = Reference::parse(image) {
+            state.errors.push(ValidationError::ActionRuns {
+                code: "invalid_docker_image".into(),
+                severity: Severity::Error,
+                path: "/runs/image".into(),
+                title: "Invalid Docker image".into(),
+                detail: Some(format!("{image} is not valid: {problem}")),
+            });
+        }
+    } else if let Some(action_dir) = action_dir {
+        validate_file_exists(image, action_dir, "/runs/image", state);
+    }
+}
+
+fn validate_file_exists(file: &str, action_dir: &Path, path: &str, state: &mut ValidationState) {
+    if !system::fs::exists(action_dir.join(file)) {
+        state.errors.push(ValidationError::ActionRuns {
+            code: "missing_action_file".into(),
+            severity: Severity::Error,
+            path: path.into(),
+            title: "Action file not found".into(),
+            detail: Some(format!(
+                "{file} does not exist in the action's directory ({})",
+                action_dir.display()
+            )),
+        });
+    }
+}
+
+fn validate_composite(runs: &Value, state: &mut ValidationState) {
+    for (i, step) in runs["steps"].as_array().into_iter().flatten().enumerate() {
+        if !step["run"].is_null() && step["shell"].is_null() {
+            state.errors.push(ValidationError::ActionRuns {
+                code: "missing_shell".into(),
+                severity: Severity::Error,
+                path: format!("/runs/steps/{i}"),
+                title: "Composite action step has no shell".into(),
+                detail: Some(
+                    "Steps in a composite action which run a script must say which shell to run it with, such as shell: bash; unlike workflows, there is no default".into(),
+                ),
+            });
+
+            // The schema requires a shell too, but doesn't say why
+            let shell_path = format!("/runs/steps/{i}/shell");
+            schemas::remove_covered(&mut state.errors, &|error| {
+                error.code() == "required" && error.path() == shell_path
+            });
+        }
+    }
+}
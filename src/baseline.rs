@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{system, validation_state::ValidationState};
+
+/// What identifies a finding in a baseline.  Line numbers are deliberately left out, so that
+/// editing a file doesn't invalidate the baseline for everything below the edit.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fingerprint {
+    pub file: String,
+    pub code: String,
+    pub path: String,
+}
+
+/// Findings which were already present when the baseline was written, and so are accepted.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Baseline {
+    findings: Vec<Fingerprint>,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let src = system::fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read baseline {}: {e}", path.display()))?;
+
+        serde_json::from_str(&src).map_err(|e| format!("Invalid baseline {}: {e}", path.display()))
+    }
+
+    pub fn save(&mut self, path: &Path) -> Result<(), String> {
+        self.findings.sort();
+        let json = serde_json::to_string_pretty(self).expect("baseline to serialize");
+
+        system::fs::write(path, &format!("{json}\n"))
+            .map_err(|e| format!("Unable to write baseline {}: {e}", path.display()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.findings.len()
+    }
+
+    /// Add all the findings in `state` to the baseline.
+    pub fn record(&mut self, state: &ValidationState) {
+        let file = file_key(state);
+
+        self.findings
+            .extend(state.errors.iter().map(|error| Fingerprint {
+                file: file.clone(),
+                code: error.code().into(),
+                path: error.path().into(),
+            }));
+    }
+
+    /// Remove the findings in `state` which are in the baseline.  Each entry in the baseline
+    /// only accepts one finding, so new findings that happen to have the same fingerprint as
+    /// an existing one are still reported.
+    pub fn filter(&mut self, state: &mut ValidationState) {
+        let file = file_key(state);
+
+        state.errors.retain(|error| {
+            let position = self.findings.iter().position(|fingerprint| {
+                fingerprint.file == file
+                    && fingerprint.code == error.code()
+                    && fingerprint.path == error.path()
+            });
+
+            match position {
+                Some(i) => {
+                    self.findings.swap_remove(i);
+                    false
+                }
+                None => true,
+            }
+        });
+    }
+
+    /// The entries for `files` which didn't match any finding, because the problem has
+    /// since been fixed.  Entries for other files can't be judged, as they weren't validated.
+    pub fn fixed<'a>(&'a self, files: &'a [String]) -> impl Iterator<Item = &'a Fingerprint> {
+        self.findings
+            .iter()
+            .filter(|fingerprint| files.contains(&fingerprint.file))
+    }
+}
+
+/// Files are identified by the path they were validated at, with `/` as the separator, so
+/// the same baseline works on every platform.
+pub fn file_key(state: &ValidationState) -> String {
+    let file = state.file_path.as_deref().unwrap_or("file");
+    let file = file.strip_prefix("./").unwrap_or(file);
+
+    file.replace('\\', "/")
+}
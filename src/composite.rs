@@ -0,0 +1,121 @@
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+use crate::{
+    expressions::{for_each_expression, references},
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// Check that a composite action's inputs and outputs line up with what its steps use and
+/// provide.
+pub fn validate_composite_io(doc: &Value, state: &mut ValidationState) {
+    if doc["runs"]["using"] != "composite" {
+        return;
+    }
+
+    let step_ids: BTreeSet<&str> = doc["runs"]["steps"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|step| step["id"].as_str())
+        .collect();
+
+    // Input names are case-insensitive
+    let declared: BTreeSet<String> = doc["inputs"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, _)| name.to_lowercase())
+        .collect();
+    let mut used = BTreeSet::new();
+    let mut indexes_inputs = false;
+
+    let mut check = |path: &str, expression: &str| {
+        for reference in references(expression) {
+            let mut parts = reference.split('.');
+            match (parts.next(), parts.next()) {
+                (Some("inputs"), Some(input)) if input != "*" => {
+                    let input = input.to_lowercase();
+                    if !declared.contains(&input) {
+                        state.errors.push(ValidationError::CompositeIo {
+                            code: "undeclared_input".into(),
+                            severity: Severity::Error,
+                            path: path.into(),
+                            title: "Undeclared input".into(),
+                            detail: Some(format!(
+                                "${{{{ {expression} }}}} refers to input {input}, which the action does not declare in inputs"
+                            )),
+                        });
+                    }
+                    used.insert(input);
+                }
+                (Some("inputs"), Some(_)) => indexes_inputs = true,
+                (Some("steps"), Some(id)) if id != "*" && !step_ids.contains(id) => {
+                    state.errors.push(ValidationError::CompositeIo {
+                        code: "unknown_step_id".into(),
+                        severity: Severity::Error,
+                        path: path.into(),
+                        title: "Unknown step".into(),
+                        detail: Some(format!(
+                            "${{{{ {expression} }}}} refers to step {id}, but no step in runs.steps has that id"
+                        )),
+                    });
+                }
+                _ => {}
+            }
+        }
+    };
+    for_each_expression(&doc["outputs"], "/outputs", &mut check);
+    for_each_expression(&doc["runs"], "/runs", &mut check);
+
+    validate_output_values(doc, state);
+
+    // Any input could be used by inputs[...], so there's no telling which are unused
+    if indexes_inputs {
+        return;
+    }
+
+    for (name, _) in doc["inputs"].as_object().into_iter().flatten() {
+        if !used.contains(&name.to_lowercase()) {
+            state.errors.push(ValidationError::CompositeIo {
+                code: "unused_input".into(),
+                severity: Severity::Warning,
+                path: format!("/inputs/{name}"),
+                title: "Unused input".into(),
+                detail: Some(format!(
+                    "Input {name} is declared, but nothing in the action uses ${{{{ inputs.{name} }}}}"
+                )),
+            });
+        }
+    }
+}
+
+/// A composite action's outputs can only get their values from the outputs of its steps.
+fn validate_output_values(doc: &Value, state: &mut ValidationState) {
+    for (name, output) in doc["outputs"].as_object().into_iter().flatten() {
+        let Some(value) = output["value"].as_str() else {
+            continue;
+        };
+
+        let mut from_step = false;
+        for_each_expression(&output["value"], "", &mut |_, expression| {
+            from_step |= references(expression).iter().any(|reference| {
+                let parts: Vec<&str> = reference.split('.').collect();
+                matches!(parts[..], ["steps", _, "outputs", _, ..])
+            });
+        });
+
+        if !from_step {
+            state.errors.push(ValidationError::CompositeIo {
+                code: "output_not_from_step".into(),
+                severity: Severity::Error,
+                path: format!("/outputs/{name}/value"),
+                title: "Output does not come from a step".into(),
+                detail: Some(format!(
+                    "Output {name} has the value {value:?}, which doesn't refer to the output of a step, such as ${{{{ steps.<id>.outputs.<name> }}}}"
+                )),
+            });
+        }
+    }
+}
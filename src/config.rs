@@ -1,35 +1,409 @@
-use clap::Parser;
-use serde::Serialize;
-use std::path::PathBuf;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-#[derive(Parser, Debug)]
+use crate::{rules::Rule, system, validation_error::Severity};
+
+#[derive(Parser, Clone, Debug)]
 #[command(
     name = "action-validator",
     about = "A validator for GitHub Action and Workflow YAML files",
-    version
+    version,
+    args_conflicts_with_subcommands = true
 )]
 pub struct CliConfig {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Be more verbose
     #[arg(short, long)]
     pub verbose: bool,
 
+    /// Read settings from FILE, rather than the .action-validator.toml (or .yml) at the top of the repository
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// How to report problems
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
     #[arg(
         long,
         help = "Use specified dir as root of glob matching, rather than the current directory"
     )]
     pub rootdir: Option<PathBuf>,
 
+    /// Accept LABEL as the label of a self-hosted runner in `runs-on` (may be repeated)
+    #[arg(long = "runner-label", value_name = "LABEL")]
+    pub runner_labels: Vec<String>,
+
+    /// Accept GROUP as the name of a runner group in `runs-on` (may be repeated)
+    #[arg(long = "runner-group", value_name = "GROUP")]
+    pub runner_groups: Vec<String>,
+
+    /// Allow actions belonging to OWNER (or OWNER/REPO) to be pinned to a tag or branch, rather than a commit SHA (may be repeated)
+    #[arg(long = "trusted-owner", value_name = "OWNER")]
+    pub trusted_owners: Vec<String>,
+
+    /// Validate workflows against the JSON Schema in FILE, rather than the one built in
+    #[arg(long, value_name = "FILE")]
+    pub workflow_schema: Option<PathBuf>,
+
+    /// Validate actions against the JSON Schema in FILE, rather than the one built in
+    #[arg(long, value_name = "FILE")]
+    pub action_schema: Option<PathBuf>,
+
+    /// Check `run` scripts with shellcheck, optionally giving the PROGRAM to run instead of the `shellcheck` found in the PATH
+    #[arg(
+        long,
+        value_name = "PROGRAM",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "shellcheck"
+    )]
+    pub shellcheck: Option<String>,
+
+    /// Don't report findings with the given CODE (may be repeated)
+    #[arg(short = 'A', long = "allow", value_name = "CODE")]
+    pub allow: Vec<String>,
+
+    /// Report findings with the given CODE as information, which doesn't cause validation to fail (may be repeated)
+    #[arg(long = "info", value_name = "CODE")]
+    pub info: Vec<String>,
+
+    /// Report findings with the given CODE as warnings (may be repeated)
+    #[arg(short = 'W', long = "warn", value_name = "CODE")]
+    pub warn: Vec<String>,
+
+    /// Report findings with the given CODE as errors, even if they're not reported by default (may be repeated)
+    #[arg(short = 'D', long = "deny", value_name = "CODE")]
+    pub deny: Vec<String>,
+
+    /// Fail validation if there are any warnings, not just errors
+    #[arg(long, overrides_with = "no_deny_warnings")]
+    pub deny_warnings: bool,
+
+    /// Only fail validation because of errors, even if the config file sets deny-warnings
+    #[arg(long, overrides_with = "deny_warnings")]
+    pub no_deny_warnings: bool,
+
+    /// Fail validation if there are more than N warnings across all files
+    #[arg(long, value_name = "N")]
+    pub max_warnings: Option<usize>,
+
+    /// Don't report findings recorded in the baseline FILE, but do report those that have since been fixed
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+
+    /// Record every finding in the baseline FILE, rather than reporting them
+    #[arg(long, value_name = "FILE")]
+    pub write_baseline: Option<PathBuf>,
+
     /// Input file
     #[arg(name = "path_to_action_yaml")]
     pub src: Vec<PathBuf>,
+
+    /// Patterns of files to validate when none are given on the command line, which can only
+    /// be set in a config file.
+    #[arg(skip)]
+    pub files: Vec<String>,
+
+    /// The directory that `files` are relative to.
+    #[arg(skip)]
+    pub files_dir: Option<PathBuf>,
+
+    /// Checks defined in a config file.
+    #[arg(skip)]
+    pub custom_rules: Vec<CustomRuleConfig>,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Work with the schemas that files are validated against
+    Schema {
+        #[command(subcommand)]
+        command: SchemaCommand,
+    },
+    /// Describe a key or value in a file, from the schema the file is validated against
+    ExplainPath {
+        /// The file the key or value is in
+        file: PathBuf,
+
+        /// A pointer to the key or value, such as `/jobs/build/runs-on`, or the line and
+        /// column it's at, such as `12:7`
+        location: String,
+    },
+    /// Run a language server, talking Language Server Protocol over stdin and stdout, which
+    /// validates files as they're edited
+    Lsp,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum SchemaCommand {
+    /// Print the built-in schemas, along with the schemastore commit they came from, or just
+    /// the schema for one type of file
+    Dump {
+        #[arg(value_enum)]
+        action_type: Option<ActionType>,
+    },
+}
+
+#[derive(ValueEnum, Deserialize, Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// A description of each problem, for people to read
+    #[default]
+    Text,
+    /// A JSON array with the result of validating each file, for programs to read
+    Json,
+}
+
+/// Names of the config files that are looked for at the top of the repository, in order of
+/// preference.
+const CONFIG_FILE_NAMES: &[&str] = &[
+    ".action-validator.toml",
+    ".action-validator.yml",
+    ".action-validator.yaml",
+];
+
+/// Settings read from a config file.  Everything in it can also be set on the command line,
+/// apart from `files`; options given on the command line take precedence.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FileConfig {
+    /// Relative to the directory the config file is in.
+    pub rootdir: Option<PathBuf>,
+    /// Globs matching the files to validate, relative to the directory the config file is in.
+    #[serde(default)]
+    pub files: Vec<String>,
+    pub format: Option<OutputFormat>,
+    /// Whether findings with each code are reported, and how seriously.
+    #[serde(default)]
+    pub rules: BTreeMap<String, Level>,
+    #[serde(default)]
+    pub deny_warnings: bool,
+    pub max_warnings: Option<usize>,
+    /// Relative to the directory the config file is in.
+    pub baseline: Option<PathBuf>,
+    #[serde(default)]
+    pub runner_labels: Vec<String>,
+    #[serde(default)]
+    pub runner_groups: Vec<String>,
+    #[serde(default)]
+    pub trusted_owners: Vec<String>,
+    pub shellcheck: Option<ShellcheckSetting>,
+    /// Relative to the directory the config file is in.
+    pub workflow_schema: Option<PathBuf>,
+    /// Relative to the directory the config file is in.
+    pub action_schema: Option<PathBuf>,
+    #[serde(default)]
+    pub custom_rules: Vec<CustomRuleConfig>,
+}
+
+#[derive(Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Allow,
+    Info,
+    Warn,
+    Deny,
+}
+
+/// `shellcheck = true` runs the `shellcheck` in the PATH, or a path can be given instead.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ShellcheckSetting {
+    Enabled(bool),
+    Program(String),
 }
 
-#[derive(Serialize, Copy, Clone, Debug)]
+/// A check that the nodes matching `path` are valid according to a JSON Schema.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct CustomRuleConfig {
+    pub code: String,
+    pub message: String,
+    /// A JSON pointer, in which `*` matches every key or index.  The whole document is
+    /// checked if it's not given.
+    #[serde(default)]
+    pub path: String,
+    /// Only check this type of file, rather than both.
+    pub applies_to: Option<ActionType>,
+    pub schema: SchemaSetting,
+}
+
+/// A schema can be given inline, or as the path to a JSON or YAML file, relative to the
+/// directory the config file is in.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum SchemaSetting {
+    File(PathBuf),
+    Inline(serde_json::Value),
+}
+
+impl FileConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let src = system::fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read config file {}: {e}", path.display()))?;
+
+        let config = if path.extension().is_some_and(|ext| ext == "toml") {
+            toml::from_str(&src).map_err(|e| e.to_string())
+        } else {
+            yaml_serde::from_str(&src).map_err(|e| e.to_string())
+        };
+
+        config.map_err(|e| format!("Invalid config file {}: {e}", path.display()))
+    }
+
+    /// Find the config file at the top of the repository (or in the current directory, if
+    /// we're not in a repository), if there is one.
+    pub fn discover() -> Option<PathBuf> {
+        let dir = system::git::toplevel().unwrap_or_else(|| PathBuf::from("."));
+
+        CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| system::fs::exists(path))
+    }
+}
+
+impl CliConfig {
+    /// Fill in anything not given on the command line from the config file, if there is one.
+    pub fn with_file_config(&self) -> Result<CliConfig, String> {
+        let Some(path) = self.config.clone().or_else(FileConfig::discover) else {
+            return Ok(self.clone());
+        };
+        let file = FileConfig::load(&path)?;
+
+        if self.verbose {
+            system::console::log(&format!("Using settings from {}", path.display()));
+        }
+
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut config = self.clone();
+
+        config.rootdir = config
+            .rootdir
+            .or_else(|| file.rootdir.map(|rootdir| dir.join(rootdir)));
+        config.format = config.format.or(file.format);
+        config.workflow_schema = config
+            .workflow_schema
+            .or_else(|| file.workflow_schema.map(|schema| dir.join(schema)));
+        config.action_schema = config
+            .action_schema
+            .or_else(|| file.action_schema.map(|schema| dir.join(schema)));
+        config.shellcheck = config.shellcheck.or(match file.shellcheck {
+            Some(ShellcheckSetting::Enabled(true)) => Some("shellcheck".into()),
+            Some(ShellcheckSetting::Program(program)) => Some(program),
+            _ => None,
+        });
+
+        config.runner_labels.extend(file.runner_labels);
+        config.runner_groups.extend(file.runner_groups);
+        config.trusted_owners.extend(file.trusted_owners);
+
+        config.deny_warnings |= file.deny_warnings && !self.no_deny_warnings;
+        config.max_warnings = config.max_warnings.or(file.max_warnings);
+        config.baseline = config
+            .baseline
+            .or_else(|| file.baseline.map(|baseline| dir.join(baseline)));
+
+        // Levels given on the command line replace those in the file
+        for (code, level) in file.rules {
+            if [&self.allow, &self.info, &self.warn, &self.deny]
+                .iter()
+                .any(|codes| codes.contains(&code))
+            {
+                continue;
+            }
+            match level {
+                Level::Allow => config.allow.push(code),
+                Level::Info => config.info.push(code),
+                Level::Warn => config.warn.push(code),
+                Level::Deny => config.deny.push(code),
+            }
+        }
+
+        config.files = file.files;
+        config.files_dir = Some(dir.to_path_buf());
+        config.custom_rules = file
+            .custom_rules
+            .into_iter()
+            .map(|mut rule| {
+                if let SchemaSetting::File(schema) = &rule.schema {
+                    rule.schema = SchemaSetting::File(dir.join(schema));
+                }
+                rule
+            })
+            .collect();
+
+        Ok(config)
+    }
+}
+
+/// The kinds of file that can be validated.
+#[derive(ValueEnum, Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
 pub enum ActionType {
-    #[serde(rename = "action")]
     Action,
-    #[serde(rename = "workflow")]
     Workflow,
+    /// `.github/dependabot.yml`
+    Dependabot,
+    /// An issue form in `.github/ISSUE_TEMPLATE`
+    IssueForm,
+    /// `.github/ISSUE_TEMPLATE/config.yml`
+    IssueConfig,
+    /// `.github/FUNDING.yml`
+    Funding,
+    /// `.github/release.yml`, which configures automatically generated release notes
+    ReleaseConfig,
+    /// The `.properties.json` file that goes with a workflow template
+    WorkflowTemplate,
+}
+
+impl ActionType {
+    /// Work out what type of file is at `path`, from its name and the directory it's in.
+    /// Anything that isn't recognised is assumed to be a workflow.
+    pub fn detect(path: &Path) -> ActionType {
+        let file_name = path.file_name().and_then(|name| name.to_str());
+        let dir_name = path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .and_then(|name| name.to_str());
+        let yaml = path
+            .extension()
+            .is_some_and(|ext| ext == "yml" || ext == "yaml");
+
+        match (dir_name, file_name) {
+            (_, Some("action.yml" | "action.yaml")) => ActionType::Action,
+            (_, Some("dependabot.yml" | "dependabot.yaml")) => ActionType::Dependabot,
+            (_, Some("FUNDING.yml" | "FUNDING.yaml")) => ActionType::Funding,
+            (Some("ISSUE_TEMPLATE"), Some("config.yml" | "config.yaml")) => ActionType::IssueConfig,
+            (Some("ISSUE_TEMPLATE"), _) if yaml => ActionType::IssueForm,
+            (Some(".github"), Some("release.yml" | "release.yaml")) => ActionType::ReleaseConfig,
+            (_, Some(name)) if name.ends_with(".properties.json") => ActionType::WorkflowTemplate,
+            _ => ActionType::Workflow,
+        }
+    }
+
+    /// What this type of file is called in messages.
+    pub fn description(self) -> &'static str {
+        match self {
+            ActionType::Action => "an Action definition",
+            ActionType::Workflow => "a Workflow definition",
+            ActionType::Dependabot => "a Dependabot configuration",
+            ActionType::IssueForm => "an issue form",
+            ActionType::IssueConfig => "an issue template configuration",
+            ActionType::Funding => "a funding configuration",
+            ActionType::ReleaseConfig => "a release notes configuration",
+            ActionType::WorkflowTemplate => "workflow template properties",
+        }
+    }
 }
 
 pub struct JsConfig<'a> {
@@ -45,6 +419,41 @@ pub struct RunConfig<'a> {
     pub src: &'a str,
     pub verbose: bool,
     pub rootdir: Option<PathBuf>,
+    pub runner_labels: &'a [String],
+    pub runner_groups: &'a [String],
+    pub trusted_owners: &'a [String],
+    pub shellcheck: Option<&'a str>,
+    pub workflow_schema: Option<&'a serde_json::Value>,
+    pub action_schema: Option<&'a serde_json::Value>,
+    pub allow: &'a [String],
+    pub info: &'a [String],
+    pub warn: &'a [String],
+    pub deny: &'a [String],
+    /// Rules to run after the built-in ones.
+    pub rules: &'a [Arc<dyn Rule>],
+}
+
+/// Codes of findings which are only reported when they're asked for with `--deny`.
+const ALLOWED_BY_DEFAULT: &[&str] = &["unpinned_action"];
+
+impl RunConfig<'_> {
+    /// How seriously to take findings with the given code, if they should be reported at all.
+    /// Unless configured otherwise, findings are as serious as the check that found them says.
+    pub fn severity(&self, code: &str, default: Severity) -> Option<Severity> {
+        let listed = |codes: &[String]| codes.iter().any(|listed| listed == code);
+
+        if listed(self.deny) {
+            Some(Severity::Error)
+        } else if listed(self.warn) {
+            Some(Severity::Warning)
+        } else if listed(self.info) {
+            Some(Severity::Info)
+        } else if listed(self.allow) || ALLOWED_BY_DEFAULT.contains(&code) {
+            None
+        } else {
+            Some(default)
+        }
+    }
 }
 
 impl<'a> From<&JsConfig<'a>> for RunConfig<'a> {
@@ -56,6 +465,17 @@ impl<'a> From<&JsConfig<'a>> for RunConfig<'a> {
             src: config.src,
             verbose: config.verbose,
             rootdir: None,
+            runner_labels: &[],
+            runner_groups: &[],
+            trusted_owners: &[],
+            shellcheck: None,
+            workflow_schema: None,
+            action_schema: None,
+            allow: &[],
+            info: &[],
+            warn: &[],
+            deny: &[],
+            rules: &[],
         }
     }
 }
@@ -0,0 +1,116 @@
+use serde_json::Value;
+
+use crate::{
+    config::{ActionType, CustomRuleConfig, SchemaSetting},
+    rules::{Rule, RuleContext},
+    schemas,
+    validation_error::Severity,
+};
+
+/// A rule from a config file, which checks the nodes matching a JSON pointer against a JSON
+/// Schema.
+pub struct CustomRule {
+    code: String,
+    message: String,
+    path: String,
+    applies_to: Option<ActionType>,
+    schema: Value,
+}
+
+impl CustomRule {
+    /// Load the rule's schema, and make sure it can be used, so that a broken rule is reported
+    /// once rather than for every file.
+    pub fn new(config: &CustomRuleConfig) -> Result<Self, String> {
+        let schema = match &config.schema {
+            SchemaSetting::Inline(schema) => schemas::check(schema)
+                .map(|_| schema.clone())
+                .map_err(|e| format!("Invalid schema: {e}")),
+            SchemaSetting::File(path) => schemas::load(path),
+        }
+        .map_err(|e| format!("Unable to load rule {}: {e}", config.code))?;
+
+        Ok(CustomRule {
+            code: config.code.clone(),
+            message: config.message.clone(),
+            path: config.path.clone(),
+            applies_to: config.applies_to,
+            schema,
+        })
+    }
+}
+
+impl Rule for CustomRule {
+    fn check(&self, context: &mut RuleContext) {
+        if self
+            .applies_to
+            .is_some_and(|action_type| action_type != context.action_type())
+        {
+            return;
+        }
+
+        let mut scope = valico::json_schema::Scope::new();
+        let validator = scope
+            .compile_and_return(self.schema.clone(), false)
+            .expect("schema to have been checked when the rule was loaded");
+
+        for (path, value) in select(context.doc(), &self.path) {
+            let state = validator.validate(value);
+
+            for error in &state.errors {
+                let detail = match error.get_detail() {
+                    Some(detail) => format!("{}: {detail}", error.get_title()),
+                    None => error.get_title().to_string(),
+                };
+                context.report(
+                    Severity::Error,
+                    &self.code,
+                    &format!("{path}{}", error.get_path()),
+                    &self.message,
+                    detail,
+                );
+            }
+        }
+    }
+}
+
+/// Find the nodes matching a JSON pointer in which `*` matches every key or index, along with
+/// the (unescaped) pointer to each one.
+fn select<'a>(doc: &'a Value, pointer: &str) -> Vec<(String, &'a Value)> {
+    let mut nodes = vec![(String::new(), doc)];
+
+    for segment in pointer.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+
+        nodes = nodes
+            .into_iter()
+            .flat_map(|(path, node)| -> Vec<(String, &Value)> {
+                match (node, segment.as_str()) {
+                    (Value::Object(map), "*") => map
+                        .iter()
+                        .map(|(key, value)| (format!("{path}/{key}"), value))
+                        .collect(),
+                    (Value::Array(items), "*") => items
+                        .iter()
+                        .enumerate()
+                        .map(|(i, value)| (format!("{path}/{i}"), value))
+                        .collect(),
+                    (Value::Object(map), key) => map
+                        .get(key)
+                        .map(|value| (format!("{path}/{key}"), value))
+                        .into_iter()
+                        .collect(),
+                    (Value::Array(items), index) => index
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| items.get(i))
+                        .map(|value| (format!("{path}/{index}"), value))
+                        .into_iter()
+                        .collect(),
+                    _ => vec![],
+                }
+            })
+            .collect();
+    }
+
+    nodes
+}
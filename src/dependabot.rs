@@ -0,0 +1,275 @@
+use serde_json::Value;
+use std::path::Path;
+
+use crate::{
+    system,
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// The files (as globs, relative to the directory being updated) that show that a directory
+/// has dependencies for each package ecosystem.  Ecosystems that aren't listed aren't checked.
+const MANIFESTS: &[(&str, &[&str])] = &[
+    ("bun", &["package.json", "bun.lock", "bun.lockb"]),
+    ("bundler", &["Gemfile", "Gemfile.lock", "*.gemspec"]),
+    ("cargo", &["Cargo.toml"]),
+    ("composer", &["composer.json"]),
+    (
+        "devcontainers",
+        &[
+            ".devcontainer.json",
+            ".devcontainer/devcontainer.json",
+            ".devcontainer/*/devcontainer.json",
+        ],
+    ),
+    ("docker", &["Dockerfile", "Dockerfile.*", "*.Dockerfile"]),
+    (
+        "docker-compose",
+        &[
+            "docker-compose.yml",
+            "docker-compose.yaml",
+            "compose.yml",
+            "compose.yaml",
+        ],
+    ),
+    ("dotnet-sdk", &["global.json"]),
+    ("elm", &["elm.json"]),
+    ("gitsubmodule", &[".gitmodules"]),
+    (
+        "github-actions",
+        &[
+            ".github/workflows/*.yml",
+            ".github/workflows/*.yaml",
+            "action.yml",
+            "action.yaml",
+        ],
+    ),
+    ("gomod", &["go.mod"]),
+    (
+        "gradle",
+        &[
+            "build.gradle",
+            "build.gradle.kts",
+            "settings.gradle",
+            "settings.gradle.kts",
+        ],
+    ),
+    ("helm", &["Chart.yaml"]),
+    ("maven", &["pom.xml"]),
+    ("mix", &["mix.exs"]),
+    ("npm", &["package.json"]),
+    (
+        "nuget",
+        &[
+            "*.csproj",
+            "*.fsproj",
+            "*.vbproj",
+            "*.sln",
+            "packages.config",
+            "Directory.Packages.props",
+            "global.json",
+        ],
+    ),
+    (
+        "pip",
+        &[
+            "requirements*.txt",
+            "requirements*.in",
+            "Pipfile",
+            "setup.py",
+            "setup.cfg",
+            "pyproject.toml",
+        ],
+    ),
+    ("pub", &["pubspec.yaml"]),
+    ("swift", &["Package.swift"]),
+    ("terraform", &["*.tf", "*.hcl"]),
+    ("uv", &["pyproject.toml", "uv.lock"]),
+];
+
+/// Check a Dependabot configuration against the repository it's in, which is rooted at
+/// `repo_root`.  Like the globs in `paths` filters, directories are looked for among the files
+/// that git tracks.
+pub fn validate_dependabot(doc: &Value, repo_root: &Path, state: &mut ValidationState) {
+    let Some(updates) = doc["updates"].as_array() else {
+        return;
+    };
+
+    validate_registries(doc, updates, state);
+    validate_duplicates(updates, state);
+
+    let files = match system::git::ls_files() {
+        Ok(files) => files,
+        Err(e) => {
+            state.errors.push(ValidationError::Dependabot {
+                code: "git_ls_files_failed".into(),
+                severity: Severity::Error,
+                path: "/updates".into(),
+                title: "Failed to get git tracked files".into(),
+                detail: Some(format!("git ls-files failed: {e}")),
+            });
+            return;
+        }
+    };
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+
+    for (i, update) in updates.iter().enumerate() {
+        let ecosystem = update["package-ecosystem"].as_str().unwrap_or("package");
+
+        for (path, directory) in directories(update, &format!("/updates/{i}")) {
+            validate_directory(ecosystem, &path, directory, repo_root, &files, state);
+        }
+    }
+}
+
+/// The directories an update applies to, with the path to each.  `directories` can contain
+/// globs, such as `/packages/*`.
+fn directories<'a>(update: &'a Value, path: &str) -> Vec<(String, &'a str)> {
+    let mut found = vec![];
+
+    if let Some(directory) = update["directory"].as_str() {
+        found.push((format!("{path}/directory"), directory));
+    }
+    for (i, directory) in update["directories"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        if let Some(directory) = directory.as_str() {
+            found.push((format!("{path}/directories/{i}"), directory));
+        }
+    }
+
+    found
+}
+
+fn validate_directory(
+    ecosystem: &str,
+    path: &str,
+    directory: &str,
+    repo_root: &Path,
+    files: &[&str],
+    state: &mut ValidationState,
+) {
+    // Directories are relative to the root of the repository, whether or not they start with
+    // `/`.  Git only knows about directories with files in them, which is also all that
+    // Dependabot can find dependencies in.
+    let relative = directory.trim_matches('/');
+    let in_directory = |glob: &str| {
+        let glob = repo_root.join(relative).join(glob).display().to_string();
+        match glob.strip_prefix("./") {
+            Some(glob) => glob.to_string(),
+            None => glob,
+        }
+    };
+    let any_match =
+        |glob: &str| compare_changes::path_matches(glob, files).is_ok_and(|m| m.is_some());
+
+    if !any_match(&in_directory("**")) {
+        state.errors.push(ValidationError::Dependabot {
+            code: "missing_dependabot_directory".into(),
+            severity: Severity::Error,
+            path: path.into(),
+            title: "Dependabot directory not found".into(),
+            detail: Some(format!(
+                "Dependabot is told to look for {ecosystem} dependencies in {directory}, which isn't in the repository"
+            )),
+        });
+        return;
+    }
+
+    let Some((_, manifests)) = MANIFESTS.iter().find(|(name, _)| *name == ecosystem) else {
+        return;
+    };
+    if !manifests
+        .iter()
+        .any(|manifest| any_match(&in_directory(manifest)))
+    {
+        state.errors.push(ValidationError::Dependabot {
+            code: "missing_dependabot_manifest".into(),
+            severity: Severity::Error,
+            path: path.into(),
+            title: "No dependencies for Dependabot to update".into(),
+            detail: Some(format!(
+                "Dependabot is told to look for {ecosystem} dependencies in {directory}, but there are none there; it looks for {}",
+                manifests.join(", ")
+            )),
+        });
+    }
+}
+
+/// Dependabot refuses to run if more than one update has the same ecosystem, directory and
+/// target branch.
+fn validate_duplicates(updates: &[Value], state: &mut ValidationState) {
+    let mut seen: Vec<(&str, String, &str, String)> = vec![];
+
+    for (i, update) in updates.iter().enumerate() {
+        let Some(ecosystem) = update["package-ecosystem"].as_str() else {
+            continue;
+        };
+        let target_branch = update["target-branch"].as_str().unwrap_or("");
+
+        for (path, directory) in directories(update, &format!("/updates/{i}")) {
+            let directory = directory.trim_matches('/');
+
+            if let Some((.., first)) = seen
+                .iter()
+                .find(|(e, d, t, _)| *e == ecosystem && d == directory && *t == target_branch)
+            {
+                let branch = match target_branch {
+                    "" => String::new(),
+                    branch => format!(", targeting {branch},"),
+                };
+                state.errors.push(ValidationError::Dependabot {
+                    code: "duplicate_dependabot_update".into(),
+                    severity: Severity::Error,
+                    path: path.clone(),
+                    title: "Duplicate Dependabot update".into(),
+                    detail: Some(format!(
+                        "{ecosystem} dependencies in /{directory}{branch} are already updated by {first}; Dependabot won't run with more than one update for the same ecosystem, directory and target branch"
+                    )),
+                });
+            } else {
+                seen.push((
+                    ecosystem,
+                    directory.to_string(),
+                    target_branch,
+                    format!("/updates/{i}"),
+                ));
+            }
+        }
+    }
+}
+
+/// Updates can only use registries that are defined at the top level.
+fn validate_registries(doc: &Value, updates: &[Value], state: &mut ValidationState) {
+    let defined = doc["registries"].as_object();
+
+    for (i, update) in updates.iter().enumerate() {
+        let registries = match &update["registries"] {
+            Value::Array(registries) => registries,
+            // "*" uses every registry that's defined
+            _ => continue,
+        };
+
+        for (j, registry) in registries.iter().enumerate() {
+            let Some(name) = registry.as_str() else {
+                continue;
+            };
+            if name == "*" || defined.is_some_and(|defined| defined.contains_key(name)) {
+                continue;
+            }
+
+            state.errors.push(ValidationError::Dependabot {
+                code: "unknown_dependabot_registry".into(),
+                severity: Severity::Error,
+                path: format!("/updates/{i}/registries/{j}"),
+                title: "Unknown Dependabot registry".into(),
+                detail: Some(format!(
+                    "Registry {name} isn't defined in the top-level registries"
+                )),
+            });
+        }
+    }
+}
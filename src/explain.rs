@@ -0,0 +1,55 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::schemas;
+
+/// What the schema a file is validated against says about a key or value in it.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    /// The pointer to what's being explained, in the same form as the `path` of findings.
+    pub path: String,
+    pub description: Option<String>,
+    /// The only values allowed, if there's a list of them.
+    pub allowed_values: Vec<String>,
+    /// Where GitHub documents it.
+    pub docs_url: Option<String>,
+}
+
+/// Explain the value at `path` from `schema`, if the schema has anything to say about it.
+pub fn explain(schema: &Value, path: &str) -> Option<Explanation> {
+    let segments: Vec<&str> = match path.strip_prefix('/') {
+        Some(path) => path.split('/').collect(),
+        None => vec![],
+    };
+    let found = schemas::at_path(schema, &segments);
+
+    let description = schemas::description(&found);
+    let allowed_values = schemas::allowed_values(&found);
+    // SchemaStore's GitHub schemas link to the docs in a comment, but descriptions often
+    // have a link in them too
+    let docs_url = found
+        .iter()
+        .filter_map(|schema| schema["$comment"].as_str())
+        .chain(description)
+        .find_map(find_url);
+
+    if description.is_none() && allowed_values.is_empty() && docs_url.is_none() {
+        return None;
+    }
+
+    Some(Explanation {
+        path: path.into(),
+        description: description.map(String::from),
+        allowed_values,
+        docs_url: docs_url.map(String::from),
+    })
+}
+
+fn find_url(text: &str) -> Option<&str> {
+    let start = text.find("https://").or_else(|| text.find("http://"))?;
+    let url = text[start..]
+        .split(|c: char| c.is_whitespace() || c == ')' || c == '"')
+        .next()?;
+
+    Some(url.trim_end_matches(['.', ',', ';', ':']))
+}
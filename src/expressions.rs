@@ -0,0 +1,127 @@
+use serde_json::Value;
+
+/// Extract the body of every `${{ ... }}` expression embedded in a string.
+pub fn expressions(s: &str) -> Vec<&str> {
+    let mut exprs = vec![];
+    let mut rest = s;
+
+    while let Some(start) = rest.find("${{") {
+        let after = &rest[start + 3..];
+        match after.find("}}") {
+            Some(end) => {
+                exprs.push(after[..end].trim());
+                rest = &after[end + 2..];
+            }
+            None => break,
+        }
+    }
+
+    exprs
+}
+
+/// Find the property references (such as `github.event.issue.title` or `matrix.os`) in an
+/// expression.  Index operations are normalised to `*`, so `github.event.commits[0].message`
+/// is returned as `github.event.commits.*.message`.  Function names and the contents of string
+/// literals are skipped.
+pub fn references(expression: &str) -> Vec<String> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut refs = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '\'' {
+            // String literals escape quotes by doubling them, which this handles naturally
+            i += 1;
+            while i < chars.len() && chars[i] != '\'' {
+                i += 1;
+            }
+            i += 1;
+        } else if is_identifier_start(c) && (i == 0 || !is_identifier_char(chars[i - 1])) {
+            let mut reference = String::new();
+
+            loop {
+                let start = i;
+                while i < chars.len() && (is_identifier_char(chars[i]) || chars[i] == '*') {
+                    i += 1;
+                }
+                reference.extend(&chars[start..i]);
+
+                while i < chars.len() && chars[i] == '[' {
+                    match chars[i..].iter().position(|&c| c == ']') {
+                        Some(len) => {
+                            reference.push_str(".*");
+                            i += len + 1;
+                        }
+                        None => i = chars.len(),
+                    }
+                }
+
+                if i + 1 < chars.len()
+                    && chars[i] == '.'
+                    && (is_identifier_start(chars[i + 1]) || chars[i + 1] == '*')
+                {
+                    reference.push('.');
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+
+            let is_function_call = chars[i..]
+                .iter()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|&c| c == '(');
+
+            if !is_function_call {
+                refs.push(reference);
+            }
+        } else {
+            i += 1;
+        }
+    }
+
+    refs
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Call `f` with the JSON pointer and body of every expression found in a document (or part of
+/// one).  Values of `if` keys are treated as expressions even when they are not wrapped in
+/// `${{ ... }}`, as GitHub evaluates them that way.
+pub fn for_each_expression<F>(value: &Value, path: &str, f: &mut F)
+where
+    F: FnMut(&str, &str),
+{
+    match value {
+        Value::String(s) => {
+            for expression in expressions(s) {
+                f(path, expression);
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                for_each_expression(item, &format!("{path}/{i}"), f);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                let item_path = format!("{path}/{key}");
+                match item.as_str() {
+                    Some(condition) if key == "if" && !condition.contains("${{") => {
+                        f(&item_path, condition.trim());
+                    }
+                    _ => for_each_expression(item, &item_path, f),
+                }
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,183 @@
+use serde_json::Value;
+
+use crate::{
+    expressions::{expressions, references},
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// Context values which can be set by whoever opens an issue or pull request, comments, or
+/// pushes a commit or branch, and so cannot be trusted.  `*` matches any single property or
+/// array index.
+const UNTRUSTED_CONTEXTS: &[&str] = &[
+    "github.head_ref",
+    "github.event.issue.title",
+    "github.event.issue.body",
+    "github.event.pull_request.title",
+    "github.event.pull_request.body",
+    "github.event.pull_request.head.ref",
+    "github.event.pull_request.head.label",
+    "github.event.pull_request.head.repo.default_branch",
+    "github.event.comment.body",
+    "github.event.review.body",
+    "github.event.review_comment.body",
+    "github.event.discussion.title",
+    "github.event.discussion.body",
+    "github.event.pages.*.page_name",
+    "github.event.commits.*.message",
+    "github.event.commits.*.author.email",
+    "github.event.commits.*.author.name",
+    "github.event.head_commit.message",
+    "github.event.head_commit.author.email",
+    "github.event.head_commit.author.name",
+    "github.event.workflow_run.head_branch",
+    "github.event.workflow_run.head_commit.message",
+    "github.event.workflow_run.head_commit.author.email",
+    "github.event.workflow_run.head_commit.author.name",
+    "github.event.workflow_run.pull_requests.*.head.ref",
+];
+
+pub fn validate_workflow_scripts(doc: &Value, state: &mut ValidationState) {
+    let Some(jobs) = doc["jobs"].as_object() else {
+        return;
+    };
+
+    for (job_name, job) in jobs {
+        let envs = [&doc["env"], &job["env"]];
+        validate_steps(
+            &job["steps"],
+            &format!("/jobs/{job_name}/steps"),
+            &envs,
+            state,
+        );
+    }
+}
+
+pub fn validate_action_scripts(doc: &Value, state: &mut ValidationState) {
+    if doc["runs"]["using"] == "composite" {
+        validate_steps(&doc["runs"]["steps"], "/runs/steps", &[], state);
+    }
+}
+
+fn validate_steps(steps: &Value, path: &str, envs: &[&Value], state: &mut ValidationState) {
+    let Some(steps) = steps.as_array() else {
+        return;
+    };
+
+    for (i, step) in steps.iter().enumerate() {
+        let mut envs = envs.to_vec();
+        envs.push(&step["env"]);
+
+        if let Some(script) = step["run"].as_str() {
+            validate_script(
+                script,
+                &format!("{path}/{i}/run"),
+                &envs,
+                Language::Shell,
+                state,
+            );
+        }
+
+        let is_github_script = step["uses"]
+            .as_str()
+            .is_some_and(|uses| uses.starts_with("actions/github-script@"));
+        if let Some(script) = step["with"]["script"].as_str().filter(|_| is_github_script) {
+            validate_script(
+                script,
+                &format!("{path}/{i}/with/script"),
+                &envs,
+                Language::JavaScript,
+                state,
+            );
+        }
+    }
+}
+
+/// What a script is written in, which determines how it should get hold of untrusted values.
+#[derive(Clone, Copy)]
+enum Language {
+    Shell,
+    JavaScript,
+}
+
+fn validate_script(
+    script: &str,
+    path: &str,
+    envs: &[&Value],
+    language: Language,
+    state: &mut ValidationState,
+) {
+    for expression in expressions(script) {
+        for reference in references(expression) {
+            let usage = |variable: &str| match language {
+                Language::Shell => format!("\"${variable}\""),
+                Language::JavaScript => format!("process.env.{variable}"),
+            };
+
+            let detail = if is_untrusted(&reference) {
+                let variable = variable_name(&reference);
+                format!(
+                    "${{{{ {expression} }}}} expands {reference} directly into the script, which lets whoever controls that value run their own code; set it in env (`{variable}: ${{{{ {expression} }}}}`) and use {} in the script instead",
+                    usage(&variable)
+                )
+            } else if let Some((name, untrusted)) = reference
+                .strip_prefix("env.")
+                .and_then(|name| Some((name, untrusted_env_source(name, envs)?)))
+            {
+                // Passing a value through an environment variable doesn't help if the variable
+                // is then expanded into the script with an expression
+                format!(
+                    "${{{{ {expression} }}}} expands {untrusted} directly into the script, which lets whoever controls that value run their own code; use {} in the script instead",
+                    usage(name)
+                )
+            } else {
+                continue;
+            };
+
+            state.errors.push(ValidationError::ScriptInjection {
+                code: "script_injection".into(),
+                severity: Severity::Error,
+                path: path.into(),
+                title: "Untrusted input in script".into(),
+                detail: Some(detail),
+            });
+        }
+    }
+}
+
+fn is_untrusted(reference: &str) -> bool {
+    UNTRUSTED_CONTEXTS.iter().any(|untrusted| {
+        let pattern: Vec<&str> = untrusted.split('.').collect();
+        let reference: Vec<&str> = reference.split('.').collect();
+
+        // Referring to part of an untrusted value is just as dangerous as the whole thing
+        reference.len() >= pattern.len()
+            && pattern
+                .iter()
+                .zip(&reference)
+                .all(|(p, r)| p == r || *p == "*")
+    })
+}
+
+/// If the named environment variable is set from an untrusted context, return that context.
+/// The innermost definition of a variable wins, as it does when the workflow runs.
+fn untrusted_env_source(name: &str, envs: &[&Value]) -> Option<String> {
+    let value = envs.iter().rev().find_map(|env| env[name].as_str())?;
+
+    expressions(value)
+        .into_iter()
+        .flat_map(references)
+        .find(|reference| is_untrusted(reference))
+}
+
+/// Come up with a reasonable name for an environment variable to hold the given value.
+fn variable_name(reference: &str) -> String {
+    let parts: Vec<&str> = reference
+        .split('.')
+        .filter(|part| !matches!(*part, "github" | "event" | "*"))
+        .collect();
+
+    parts[parts.len().saturating_sub(2)..]
+        .join("_")
+        .to_uppercase()
+}
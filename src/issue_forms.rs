@@ -0,0 +1,170 @@
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::{
+    schemas,
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// Check the elements of an issue form for problems that make GitHub refuse to use it.
+/// GitHub doesn't say why it's ignoring a form, it just leaves it out of the template
+/// chooser, so these are all errors.
+pub fn validate_issue_form(doc: &Value, state: &mut ValidationState) {
+    let Some(body) = doc["body"].as_array() else {
+        return;
+    };
+
+    let mut ids: HashMap<&str, usize> = HashMap::new();
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    for (i, element) in body.iter().enumerate() {
+        if let Some(id) = element["id"].as_str() {
+            if let Some(first) = ids.get(id) {
+                state.errors.push(issue_form_error(
+                    "duplicate_issue_form_id",
+                    format!("/body/{i}/id"),
+                    "Duplicate issue form id",
+                    format!(
+                        "The element at /body/{first} already has the id {id}; GitHub won't use an issue form unless every id in it is unique"
+                    ),
+                ));
+            } else {
+                ids.insert(id, i);
+            }
+        }
+
+        // Markdown elements don't have a label, they're just text
+        if element["type"] != "markdown" {
+            if let Some(label) = element["attributes"]["label"].as_str() {
+                if let Some(first) = labels.get(label) {
+                    state.errors.push(issue_form_error(
+                        "duplicate_issue_form_label",
+                        format!("/body/{i}/attributes/label"),
+                        "Duplicate issue form label",
+                        format!(
+                            "The element at /body/{first} already has the label {label:?}; GitHub won't use an issue form unless every label in it is unique"
+                        ),
+                    ));
+                } else {
+                    labels.insert(label, i);
+                }
+            }
+        }
+
+        if element["type"] == "dropdown" {
+            validate_dropdown(element, i, state);
+        }
+    }
+
+    if !body.is_empty() && body.iter().all(|element| element["type"] == "markdown") {
+        state.errors.push(issue_form_error(
+            "issue_form_without_inputs",
+            "/body".into(),
+            "Issue form has nothing to fill in",
+            "Every element of the issue form is markdown; GitHub won't use an issue form unless it has at least one input, textarea, dropdown or checkboxes element".into(),
+        ));
+    }
+}
+
+fn validate_dropdown(element: &Value, i: usize, state: &mut ValidationState) {
+    let options = match element["attributes"]["options"].as_array() {
+        Some(options) if !options.is_empty() => options,
+        _ => {
+            state.errors.push(issue_form_error(
+                "issue_form_dropdown_without_options",
+                format!("/body/{i}/attributes"),
+                "Dropdown without options",
+                "GitHub won't use an issue form with a dropdown that has no options to choose from"
+                    .into(),
+            ));
+
+            // The schema says so too, but not what it means for the form
+            let options_path = format!("/body/{i}/attributes/options");
+            schemas::remove_covered(&mut state.errors, &|error| {
+                matches!(error.code(), "min_items" | "required") && error.path() == options_path
+            });
+            return;
+        }
+    };
+
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for (j, option) in options.iter().enumerate() {
+        let Some(option) = option.as_str() else {
+            continue;
+        };
+        if let Some(first) = seen.get(option) {
+            state.errors.push(issue_form_error(
+                "duplicate_issue_form_option",
+                format!("/body/{i}/attributes/options/{j}"),
+                "Duplicate dropdown option",
+                format!(
+                    "The dropdown already has the option {option:?} at /body/{i}/attributes/options/{first}; GitHub won't use an issue form unless every option in a dropdown is unique"
+                ),
+            ));
+        } else {
+            seen.insert(option, j);
+        }
+    }
+}
+
+/// Check the contact links in the issue template chooser's `config.yml`.  A link that GitHub
+/// can't use makes it ignore the whole file.
+pub fn validate_issue_config(doc: &Value, state: &mut ValidationState) {
+    let Some(links) = doc["contact_links"].as_array() else {
+        return;
+    };
+
+    for (i, link) in links.iter().enumerate() {
+        for key in ["name", "url", "about"] {
+            if link[key]
+                .as_str()
+                .is_none_or(|value| value.trim().is_empty())
+            {
+                state.errors.push(issue_form_error(
+                    "invalid_contact_link",
+                    format!("/contact_links/{i}"),
+                    "Invalid contact link",
+                    format!("Contact links need a {key}, but this one doesn't have one"),
+                ));
+
+                let key_path = format!("/contact_links/{i}/{key}");
+                schemas::remove_covered(&mut state.errors, &|error| {
+                    error.code() == "required" && error.path() == key_path
+                });
+            }
+        }
+
+        if let Some(url) = link["url"].as_str().filter(|url| !url.trim().is_empty()) {
+            if !is_web_url(url) {
+                state.errors.push(issue_form_error(
+                    "invalid_contact_link",
+                    format!("/contact_links/{i}/url"),
+                    "Invalid contact link",
+                    format!("Contact links have to go to a web page, starting with https:// or http://, but this one goes to {url:?}"),
+                ));
+            }
+        }
+    }
+}
+
+fn is_web_url(url: &str) -> bool {
+    let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+
+    !host.is_empty() && !host.contains(char::is_whitespace)
+}
+
+fn issue_form_error(code: &str, path: String, title: &str, detail: String) -> ValidationError {
+    ValidationError::IssueForm {
+        code: code.into(),
+        severity: Severity::Error,
+        path,
+        title: title.into(),
+        detail: Some(detail),
+    }
+}
@@ -1,17 +1,42 @@
+mod action_runs;
+mod baseline;
+mod composite;
 mod config;
+mod custom_rules;
+mod dependabot;
+mod explain;
+mod expressions;
+mod injection;
+mod issue_forms;
+#[cfg(not(feature = "js"))]
+mod lsp;
+mod matrix;
+mod permissions;
+mod privileged_checkout;
+mod rules;
+mod runners;
 mod schemas;
+mod shellcheck;
+mod source_map;
+mod suppressions;
 mod system;
+mod uses;
 mod utils;
 mod validation_error;
 mod validation_state;
+mod validator;
+mod workflow_commands;
 
-use config::{ActionType, RunConfig};
+use config::RunConfig;
+use source_map::SourceMap;
 use std::path::PathBuf;
-use validation_error::ValidationError;
-use validation_state::ValidationState;
 
-pub use crate::config::CliConfig;
-use crate::schemas::{validate_as_action, validate_as_workflow};
+pub use crate::config::{ActionType, CliConfig};
+pub use crate::explain::Explanation;
+pub use crate::rules::{Rule, RuleContext};
+pub use crate::validation_error::{ParseErrorLocation, Severity, ValidationError};
+pub use crate::validation_state::ValidationState;
+pub use crate::validator::Validator;
 
 #[cfg(feature = "js")]
 mod js {
@@ -89,9 +114,16 @@ mod js {
 }
 
 pub mod cli {
+    use clap::ValueEnum as _;
+    use std::path::{Path, PathBuf};
+
     use crate::{
-        config::{ActionType, RunConfig},
-        system, CliConfig,
+        baseline::{self, Baseline},
+        config::{ActionType, Command, OutputFormat, SchemaCommand},
+        custom_rules::CustomRule,
+        matching_files, schemas, system,
+        validation_error::Severity,
+        CliConfig, Validator,
     };
 
     pub enum RunResult {
@@ -100,49 +132,147 @@ pub mod cli {
     }
 
     pub fn run(config: &CliConfig) -> RunResult {
+        if let Some(Command::Schema { command }) = &config.command {
+            return run_schema_command(command);
+        }
+
+        let config = match config.with_file_config() {
+            Ok(config) => config,
+            Err(e) => {
+                system::console::error(&e);
+                return RunResult::Failure;
+            }
+        };
+
+        match &config.command {
+            Some(Command::Lsp) => return run_language_server(&config),
+            Some(Command::ExplainPath { file, location }) => {
+                return run_explain_path(&config, file, location)
+            }
+            _ => {}
+        }
+
+        let paths = if config.src.is_empty() && !config.files.is_empty() {
+            match discover_files(&config) {
+                Ok(paths) => paths,
+                Err(e) => {
+                    system::console::error(&e);
+                    return RunResult::Failure;
+                }
+            }
+        } else {
+            config.src.clone()
+        };
+
+        // When writing a baseline, every finding needs recording, including those already in
+        // the old one
+        let mut baseline = match &config.baseline {
+            Some(path) if config.write_baseline.is_none() => match Baseline::load(path) {
+                Ok(baseline) => Some(baseline),
+                Err(e) => {
+                    system::console::error(&e);
+                    return RunResult::Failure;
+                }
+            },
+            _ => None,
+        };
+        let mut recorded = Baseline::default();
+        let mut validated = vec![];
+
+        let validator = match build_validator(&config) {
+            Ok(validator) => validator,
+            Err(e) => {
+                system::console::error(&e);
+                return RunResult::Failure;
+            }
+        };
+        let format = config.format.unwrap_or_default();
+        let deny_warnings = config.deny_warnings;
+        let write_baseline = config.write_baseline.is_some();
         let mut success = true;
+        let mut states = vec![];
+        let mut total_warnings = 0;
 
-        for path in &config.src {
-            let file_name = match path.file_name() {
-                Some(file_name) => file_name.to_str(),
-                None => {
-                    eprintln!("Unable to derive file name from src!");
+        for path in &paths {
+            let mut state = match validator.validate_file(path) {
+                Ok(state) => state,
+                Err(e) => {
+                    system::console::error(&e);
                     success = false;
                     continue;
                 }
             };
+            if write_baseline {
+                recorded.record(&state);
+                continue;
+            }
+            if let Some(baseline) = &mut baseline {
+                baseline.filter(&mut state);
+            }
+            validated.push(baseline::file_key(&state));
 
-            let src = &match system::fs::read_to_string(path) {
-                Ok(src) => src,
-                Err(err) => {
+            let warnings = state.count(Severity::Warning);
+            total_warnings += warnings;
+
+            let failed = !state.is_valid() || (deny_warnings && warnings > 0);
+            success &= !failed;
+
+            if format == OutputFormat::Text && !state.errors.is_empty() {
+                let fmt_state = format!("{state:#?}");
+                let path = state.file_path.as_deref().unwrap_or("file");
+
+                if !failed {
+                    let found = if warnings > 0 { "warnings" } else { "notes" };
                     system::console::error(&format!(
-                        "Unable to read file {}: {err}",
-                        path.display()
+                        "Validation of {path} passed with {found}: {fmt_state}"
                     ));
-                    success = false;
-                    continue;
+                } else {
+                    system::console::log(&format!("Fatal error validating {path}"));
+                    system::console::error(&format!("Validation failed: {fmt_state}"));
                 }
-            };
+                continue;
+            }
 
-            let config = RunConfig {
-                file_path: Some(path.to_str().unwrap()),
-                file_name,
-                action_type: match file_name {
-                    Some("action.yml") | Some("action.yaml") => ActionType::Action,
-                    _ => ActionType::Workflow,
-                },
-                src,
-                verbose: config.verbose,
-                rootdir: config.rootdir.clone(),
-            };
+            states.push(state);
+        }
 
-            let state = crate::run(&config);
+        if let Some(path) = &config.write_baseline {
+            if let Err(e) = recorded.save(path) {
+                system::console::error(&e);
+                return RunResult::Failure;
+            }
+            system::console::error(&format!(
+                "Recorded {} findings in the baseline {}",
+                recorded.len(),
+                path.display()
+            ));
+        }
 
-            if !state.is_valid() {
-                let fmt_state = format!("{state:#?}");
-                let path = state.file_path.unwrap_or("file".into());
-                system::console::log(&format!("Fatal error validating {path}"));
-                system::console::error(&format!("Validation failed: {fmt_state}"));
+        if let Some(baseline) = &baseline {
+            for fixed in baseline.fixed(&validated) {
+                let path = if fixed.path.is_empty() {
+                    "the top level"
+                } else {
+                    &fixed.path
+                };
+                system::console::error(&format!(
+                    "{} in {} at {path} is in the baseline, but has been fixed; update the baseline with --write-baseline",
+                    fixed.code, fixed.file
+                ));
+            }
+        }
+
+        if format == OutputFormat::Json && !write_baseline {
+            system::console::log(
+                &serde_json::to_string_pretty(&states).expect("validation states to serialize"),
+            );
+        }
+
+        if let Some(max_warnings) = config.max_warnings {
+            if total_warnings > max_warnings {
+                system::console::error(&format!(
+                    "Found {total_warnings} warnings, which is more than the {max_warnings} allowed"
+                ));
                 success = false;
             }
         }
@@ -153,39 +283,249 @@ pub mod cli {
             RunResult::Failure
         }
     }
-}
 
-fn run(config: &RunConfig) -> ValidationState {
-    let file_name = config.file_name.unwrap_or("file");
-    let doc = yaml_serde::from_str(config.src);
+    /// Set up a validator with everything from the config that has to be loaded first.
+    fn build_validator(config: &CliConfig) -> Result<Validator, String> {
+        let mut validator = Validator::from(config);
 
-    let mut state = match doc {
-        Err(err) => ValidationState {
-            action_type: Some(config.action_type),
-            file_path: Some(file_name.to_string()),
-            errors: vec![err.into()],
-        },
-        Ok(doc) => match config.action_type {
-            ActionType::Action => {
-                if config.verbose {
-                    system::console::log(&format!("Treating {file_name} as an Action definition"));
+        if let Some(path) = &config.workflow_schema {
+            validator = validator.workflow_schema(schemas::load(path)?)?;
+        }
+        if let Some(path) = &config.action_schema {
+            validator = validator.action_schema(schemas::load(path)?)?;
+        }
+        for rule in &config.custom_rules {
+            validator = validator.rule(CustomRule::new(rule)?);
+        }
+
+        Ok(validator)
+    }
+
+    fn run_explain_path(config: &CliConfig, file: &Path, location: &str) -> RunResult {
+        let validator = match build_validator(config) {
+            Ok(validator) => validator,
+            Err(e) => {
+                system::console::error(&e);
+                return RunResult::Failure;
+            }
+        };
+        let action_type = ActionType::detect(file);
+
+        let explanation = if location.is_empty() || location.starts_with('/') {
+            validator.explain_path(action_type, location)
+        } else {
+            let Some((line, column)) = location
+                .split_once(':')
+                .and_then(|(line, column)| Some((line.parse().ok()?, column.parse().ok()?)))
+            else {
+                system::console::error(&format!(
+                    "{location} is neither a pointer (like /jobs/build) nor a line and column (like 12:7)"
+                ));
+                return RunResult::Failure;
+            };
+            let src = match system::fs::read_to_string(file) {
+                Ok(src) => src,
+                Err(e) => {
+                    system::console::error(&format!("Unable to read file {}: {e}", file.display()));
+                    return RunResult::Failure;
                 }
-                validate_as_action(&doc)
+            };
+            validator.explain_position(action_type, &src, line, column)
+        };
+
+        let Some(explanation) = explanation else {
+            system::console::error(&format!(
+                "The schema for {} has nothing to say about {location} in {}",
+                action_type.description(),
+                file.display()
+            ));
+            return RunResult::Failure;
+        };
+
+        system::console::log(&explanation.path);
+        if let Some(description) = &explanation.description {
+            system::console::log(&format!("\n{description}"));
+        }
+        if !explanation.allowed_values.is_empty() {
+            system::console::log(&format!(
+                "\nAllowed values: {}",
+                explanation.allowed_values.join(", ")
+            ));
+        }
+        if let Some(url) = &explanation.docs_url {
+            system::console::log(&format!("\nDocumentation: {url}"));
+        }
+
+        RunResult::Success
+    }
+
+    #[cfg(not(feature = "js"))]
+    fn run_language_server(config: &CliConfig) -> RunResult {
+        // Anything logged would get mixed up with the protocol on stdout
+        let validator = match build_validator(config) {
+            Ok(validator) => validator.verbose(false),
+            Err(e) => {
+                system::console::error(&e);
+                return RunResult::Failure;
+            }
+        };
+
+        match crate::lsp::serve(&validator) {
+            Ok(()) => RunResult::Success,
+            Err(e) => {
+                system::console::error(&e);
+                RunResult::Failure
+            }
+        }
+    }
+
+    #[cfg(feature = "js")]
+    fn run_language_server(_config: &CliConfig) -> RunResult {
+        system::console::error(
+            "The language server isn't available in the JavaScript version of action-validator",
+        );
+        RunResult::Failure
+    }
+
+    fn run_schema_command(command: &SchemaCommand) -> RunResult {
+        match command {
+            SchemaCommand::Dump {
+                action_type: Some(action_type),
+            } => {
+                let Some(schema) = schemas::embedded(*action_type) else {
+                    system::console::error(&format!(
+                        "There is no built-in schema for {}",
+                        action_type.description()
+                    ));
+                    return RunResult::Failure;
+                };
+                // The schema is printed exactly as it is in schemastore, so it can be compared
+                // with other versions
+                system::console::log(String::from_utf8_lossy(schema).trim_end());
+                system::console::error(&format!(
+                    "From schemastore commit {}",
+                    schemas::SCHEMASTORE_COMMIT
+                ));
             }
-            ActionType::Workflow => {
-                if config.verbose {
-                    system::console::log(&format!("Treating {file_name} as a Workflow definition"));
+            SchemaCommand::Dump { action_type: None } => {
+                let mut dump = serde_json::Map::new();
+                dump.insert(
+                    "schemastore-commit".into(),
+                    schemas::SCHEMASTORE_COMMIT.into(),
+                );
+                for action_type in ActionType::value_variants() {
+                    if let Some(schema) = schemas::embedded(*action_type) {
+                        let name = serde_json::to_value(action_type)
+                            .expect("action type to serialize")
+                            .as_str()
+                            .expect("action type to serialize as a string")
+                            .to_string();
+                        dump.insert(name, schemas::parse_embedded(schema));
+                    }
                 }
-                // TODO: Re-enable path and job validation
-                let mut state = validate_as_workflow(&doc);
+                system::console::log(
+                    &serde_json::to_string_pretty(&dump).expect("schemas to serialize"),
+                );
+            }
+        }
+
+        RunResult::Success
+    }
 
-                validate_paths(&doc, config.rootdir.as_ref(), &mut state);
-                validate_job_needs(&doc, &mut state);
+    /// Find the files matching the `files` patterns from the config file.  As with `paths`
+    /// filters in workflows, a pattern starting with `!` excludes the files it matches.
+    fn discover_files(config: &CliConfig) -> Result<Vec<PathBuf>, String> {
+        let dir = config.files_dir.as_deref().unwrap_or(Path::new("."));
+        let files = system::git::ls_files_in(dir)
+            .map_err(|e| format!("Unable to find files to validate: {e}"))?;
+        let file_refs: Vec<&str> = files.iter().map(String::as_str).collect();
+
+        let mut included = vec![false; files.len()];
+        for pattern in &config.files {
+            let (negated, glob) = match pattern.strip_prefix('!') {
+                Some(glob) => (true, glob),
+                None => (false, pattern.as_str()),
+            };
 
-                state
+            for i in matching_files(glob, &file_refs)
+                .map_err(|e| format!("Invalid pattern {pattern} in files: {e}"))?
+            {
+                included[i] = !negated;
             }
-        },
+        }
+
+        // Report files relative to the current directory, where possible, as if they'd been
+        // given on the command line
+        let cwd = std::env::current_dir().unwrap_or_default();
+        Ok(files
+            .iter()
+            .zip(included)
+            .filter(|(_, included)| *included)
+            .map(|(file, _)| {
+                let path = dir.join(file);
+                match path.strip_prefix(&cwd) {
+                    Ok(relative) => relative.to_path_buf(),
+                    Err(_) => path.strip_prefix("./").unwrap_or(&path).to_path_buf(),
+                }
+            })
+            .collect())
+    }
+}
+
+fn run(config: &RunConfig) -> ValidationState {
+    match yaml_serde::from_str(config.src) {
+        Ok(doc) => run_doc(config, &doc),
+        Err(err) => {
+            let mut errors = vec![err.into()];
+            apply_levels(config, &mut errors);
+
+            ValidationState {
+                action_type: Some(config.action_type),
+                file_path: config.file_path.map(|file_name| file_name.to_string()),
+                errors,
+            }
+        }
+    }
+}
+
+/// Validate a document that has already been parsed.  Anything that needs the source (such
+/// as suppression comments, and locations within scripts) comes from `config.src`, which can
+/// be empty.
+fn run_doc(config: &RunConfig, doc: &serde_json::Value) -> ValidationState {
+    let file_name = config.file_name.unwrap_or("file");
+    let source_map = SourceMap::new(config.src);
+
+    if config.verbose {
+        system::console::log(&format!(
+            "Treating {file_name} as {}",
+            config.action_type.description()
+        ));
+    }
+    let schema = match config.action_type {
+        ActionType::Action => config.action_schema,
+        ActionType::Workflow => config.workflow_schema,
+        _ => None,
+    };
+    let mut state = schemas::validate(doc, config.action_type, schema);
+
+    let mut context = RuleContext {
+        doc,
+        config,
+        source_map: &source_map,
+        state: &mut state,
     };
+    for rule in rules::BUILTIN
+        .iter()
+        .copied()
+        .chain(config.rules.iter().map(|rule| rule.as_ref()))
+    {
+        rule.check(&mut context);
+    }
+
+    apply_levels(config, &mut state.errors);
+    let mut problems = suppressions::apply(config.src, &source_map, &mut state.errors);
+    apply_levels(config, &mut problems);
+    state.errors.extend(problems);
 
     state.action_type = Some(config.action_type);
     state.file_path = config.file_path.map(|file_name| file_name.to_string());
@@ -193,6 +533,20 @@ fn run(config: &RunConfig) -> ValidationState {
     state
 }
 
+/// Set the severity of each finding to the level it has been configured at, dropping those
+/// that are allowed.
+fn apply_levels(config: &RunConfig, errors: &mut Vec<ValidationError>) {
+    errors.retain_mut(
+        |error| match config.severity(error.code(), error.severity()) {
+            Some(severity) => {
+                error.set_severity(severity);
+                true
+            }
+            None => false,
+        },
+    );
+}
+
 fn validate_paths(doc: &serde_json::Value, rootdir: Option<&PathBuf>, state: &mut ValidationState) {
     validate_globs(
         &doc["on"]["push"]["paths"],
@@ -226,65 +580,184 @@ fn validate_globs(
     rootdir: Option<&PathBuf>,
     state: &mut ValidationState,
 ) {
-    if globs.is_null() {
+    // Anything other than a list of strings is reported by the schema, and the rules run
+    // whether or not the document matches it
+    let Some(globs) = globs.as_array() else {
         return;
-    }
+    };
+
+    let git_files = match system::git::ls_files() {
+        Ok(files) => files,
+        Err(e) => {
+            state.errors.push(ValidationError::InvalidGlob {
+                code: "git_ls_files_failed".into(),
+                severity: Severity::Error,
+                path: path.into(),
+                title: "Failed to get git tracked files".into(),
+                detail: Some(format!("git ls-files failed: {e}")),
+            });
+            return;
+        }
+    };
 
-    if let Some(globs) = globs.as_array() {
-        let git_files = match system::git::ls_files() {
-            Ok(files) => files,
+    let git_file_refs: Vec<&str> = git_files.iter().map(|s| s.as_str()).collect();
+    let mut matched = Vec::with_capacity(globs.len());
+
+    for (i, g) in globs.iter().enumerate() {
+        let Some(glob) = g.as_str() else {
+            continue;
+        };
+        let negated = glob.starts_with('!');
+        let pattern = if negated {
+            glob.chars().skip(1).collect()
+        } else {
+            glob.to_string()
+        };
+
+        let pattern = if let Some(rootdir) = rootdir {
+            rootdir.join(pattern).display().to_string()
+        } else {
+            pattern
+        };
+
+        match compare_changes::path_matches(&pattern, &git_file_refs) {
+            Ok(Some(_)) => matched.push((i, negated, pattern)),
+            Ok(None) => {
+                state.errors.push(ValidationError::NoFilesMatchingGlob {
+                    code: "glob_not_matched".into(),
+                    severity: Severity::Error,
+                    path: format!("{path}/{i}"),
+                    title: "Glob does not match any files".into(),
+                    detail: Some(format!("Glob {g} in {path} does not match any files")),
+                });
+            }
             Err(e) => {
                 state.errors.push(ValidationError::InvalidGlob {
-                    code: "git_ls_files_failed".into(),
-                    path: path.into(),
-                    title: "Failed to get git tracked files".into(),
-                    detail: Some(format!("git ls-files failed: {e}")),
+                    code: "invalid_glob".into(),
+                    severity: Severity::Error,
+                    path: format!("{path}/{i}"),
+                    title: "Glob does not match any files".into(),
+                    detail: Some(format!("Glob {g} in {path} is invalid: {e}")),
                 });
-                return;
             }
         };
+    }
 
-        let git_file_refs: Vec<&str> = git_files.iter().map(|s| s.as_str()).collect();
+    validate_glob_order(globs, &matched, &git_file_refs, path, state);
+}
 
-        for g in globs {
-            let glob = g.as_str().expect("glob to be a string");
-            let pattern = if glob.starts_with('!') {
-                glob.chars().skip(1).collect()
-            } else {
-                glob.to_string()
-            };
+/// Find the indices of every file that matches the given pattern.
+fn matching_files(pattern: &str, files: &[&str]) -> Result<Vec<usize>, compare_changes::Error> {
+    let mut matched = vec![];
+    let mut offset = 0;
 
-            let pattern = if let Some(rootdir) = rootdir {
-                rootdir.join(pattern).display().to_string()
-            } else {
-                pattern
-            };
+    while let Some(i) = compare_changes::path_matches(pattern, &files[offset..])? {
+        matched.push(offset + i);
+        offset += i + 1;
+    }
 
-            match compare_changes::path_matches(&pattern, &git_file_refs) {
-                Ok(matched_index) => {
-                    if matched_index.is_none() {
-                        state.errors.push(ValidationError::NoFilesMatchingGlob {
-                            code: "glob_not_matched".into(),
-                            path: path.into(),
-                            title: "Glob does not match any files".into(),
-                            detail: Some(format!("Glob {g} in {path} does not match any files")),
-                        });
-                    }
-                }
-                Err(e) => {
-                    state.errors.push(ValidationError::InvalidGlob {
-                        code: "invalid_glob".into(),
-                        path: path.into(),
-                        title: "Glob does not match any files".into(),
-                        detail: Some(format!("Glob {g} in {path} is invalid: {e}")),
-                    });
-                }
-            };
+    Ok(matched)
+}
+
+/// GitHub evaluates a filter's globs in order, with each negated glob removing files matched by
+/// the globs before it, and later globs able to add those files back.  Individually-valid globs
+/// can therefore still be useless in combination, which is what we look for here.
+///
+/// `matched` holds the index, negation and pattern of each glob that matches at least one file.
+fn validate_glob_order(
+    globs: &[serde_json::Value],
+    matched: &[(usize, bool, String)],
+    files: &[&str],
+    path: &str,
+    state: &mut ValidationState,
+) {
+    // Matching every file against every glob compiles a glob once per match, which is far too
+    // slow for a large repo.  Only files that a negated glob matches can end up excluded, so
+    // those are the only ones we need to follow glob by glob; for the rest, it's enough to
+    // know whether a glob matches any of them at all.
+    let mut excludable = vec![false; files.len()];
+    for (_, _, pattern) in matched.iter().filter(|(_, negated, _)| *negated) {
+        for f in matching_files(pattern, files).unwrap_or_default() {
+            excludable[f] = true;
         }
-    } else {
-        unreachable!(
-            "validate_globs called on globs object with invalid type: must be array or null"
-        )
+    }
+
+    if !excludable.contains(&true) {
+        return;
+    }
+
+    let mut candidates = vec![];
+    let mut others = vec![];
+    for (&file, excludable) in files.iter().zip(excludable) {
+        if excludable {
+            candidates.push(file);
+        } else {
+            others.push(file);
+        }
+    }
+
+    // For each glob: the candidates it matches, and whether it also matches a file that no
+    // negated glob can exclude
+    let matches: Vec<(Vec<usize>, bool)> = matched
+        .iter()
+        .map(|(_, negated, pattern)| {
+            let elsewhere = !negated
+                && compare_changes::path_matches(pattern, &others).is_ok_and(|m| m.is_some());
+            (
+                matching_files(pattern, &candidates).unwrap_or_default(),
+                elsewhere,
+            )
+        })
+        .collect();
+
+    let mut included = vec![false; candidates.len()];
+
+    for (&(i, negated, _), (matched_files, _)) in matched.iter().zip(&matches) {
+        if negated && !matched_files.iter().any(|&f| included[f]) {
+            state.errors.push(ValidationError::IneffectiveGlob {
+                code: "negated_glob_excludes_nothing".into(),
+                severity: Severity::Warning,
+                path: format!("{path}/{i}"),
+                title: "Negated glob excludes nothing".into(),
+                detail: Some(format!(
+                    "Glob {} in {path} does not exclude any files matched by the globs before it",
+                    globs[i]
+                )),
+            });
+        }
+
+        for &f in matched_files {
+            included[f] = !negated;
+        }
+    }
+
+    for (&(i, negated, _), (matched_files, elsewhere)) in matched.iter().zip(&matches) {
+        if !negated && !elsewhere && !matched_files.iter().any(|&f| included[f]) {
+            state.errors.push(ValidationError::IneffectiveGlob {
+                code: "glob_shadowed".into(),
+                severity: Severity::Warning,
+                path: format!("{path}/{i}"),
+                title: "Glob is shadowed by negated globs".into(),
+                detail: Some(format!(
+                    "Every file matched by glob {} in {path} is excluded by a later negated glob",
+                    globs[i]
+                )),
+            });
+        }
+    }
+
+    let included_elsewhere = matches.iter().any(|(_, elsewhere)| *elsewhere);
+
+    if !included_elsewhere && !included.contains(&true) {
+        state.errors.push(ValidationError::NoFilesMatchingGlob {
+            code: "filter_not_matched".into(),
+            severity: Severity::Warning,
+            path: path.into(),
+            title: "Globs do not match any files".into(),
+            detail: Some(format!(
+                "Globs in {path} do not match any files once negated globs are applied"
+            )),
+        });
     }
 }
 
@@ -299,6 +772,7 @@ fn validate_job_needs(doc: &serde_json::Value, state: &mut ValidationState) {
     fn handle_unresolved_job(job_name: &String, needs_str: &str, state: &mut ValidationState) {
         state.errors.push(ValidationError::UnresolvedJob {
             code: "unresolved_job".into(),
+            severity: Severity::Error,
             path: format!("/jobs/{job_name}/needs"),
             title: "Unresolved job".into(),
             detail: Some(format!("unresolved job {needs_str}")),
@@ -0,0 +1,479 @@
+use lsp_server::{
+    Connection, ErrorCode, ExtractError, Message, Notification, Request, RequestId, Response,
+};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _,
+        PublishDiagnostics,
+    },
+    request::{Completion, HoverRequest, Request as _},
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, Diagnostic,
+    DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, Documentation, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, MarkupContent, MarkupKind, NumberOrString, Position,
+    PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Uri,
+};
+use serde_json::Value;
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::{
+    config::ActionType,
+    schemas,
+    source_map::SourceMap,
+    system,
+    validation_error::{ParseErrorLocation, Severity, ValidationError},
+    Validator,
+};
+
+/// Run a language server over stdin and stdout until the client shuts it down, validating
+/// each document as it changes.
+pub fn serve(validator: &Validator) -> Result<(), String> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![":".into(), "-".into()]),
+            ..Default::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..Default::default()
+    };
+    connection
+        .initialize(serde_json::to_value(capabilities).expect("capabilities to serialize"))
+        .map_err(|e| format!("Language server failed to start: {e}"))?;
+
+    let mut server = Server {
+        validator,
+        connection: &connection,
+        documents: HashMap::new(),
+    };
+    server.run()?;
+    drop(connection);
+
+    io_threads
+        .join()
+        .map_err(|e| format!("Language server failed to stop: {e}"))
+}
+
+struct Server<'a> {
+    validator: &'a Validator,
+    connection: &'a Connection,
+    /// The text of each open document, which is what gets validated, rather than whatever
+    /// has been saved.
+    documents: HashMap<Uri, String>,
+}
+
+impl Server<'_> {
+    fn run(&mut self) -> Result<(), String> {
+        for message in &self.connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if self
+                        .connection
+                        .handle_shutdown(&request)
+                        .map_err(|e| e.to_string())?
+                    {
+                        return Ok(());
+                    }
+                    self.request(request)?;
+                }
+                Message::Notification(notification) => self.notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn request(&mut self, request: Request) -> Result<(), String> {
+        let id = request.id.clone();
+        let response = match request.method.as_str() {
+            Completion::METHOD => match request.extract::<CompletionParams>(Completion::METHOD) {
+                Ok((id, params)) => Response::new_ok(id, self.completions(&params)),
+                Err(e) => invalid_params(id, e),
+            },
+            HoverRequest::METHOD => match request.extract::<HoverParams>(HoverRequest::METHOD) {
+                Ok((id, params)) => Response::new_ok(id, self.hover(&params)),
+                Err(e) => invalid_params(id, e),
+            },
+            method => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("{method} isn't supported"),
+            ),
+        };
+
+        self.send(response)
+    }
+
+    fn notification(&mut self, notification: Notification) -> Result<(), String> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Some(params) = extract::<DidOpenTextDocumentParams>(notification) else {
+                    return Ok(());
+                };
+                let document = params.text_document;
+                self.documents.insert(document.uri.clone(), document.text);
+                self.publish(&document.uri, Some(document.version))
+            }
+            DidChangeTextDocument::METHOD => {
+                let Some(params) = extract::<DidChangeTextDocumentParams>(notification) else {
+                    return Ok(());
+                };
+                // With full sync, each change is the whole of the document
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    let document = params.text_document;
+                    self.documents.insert(document.uri.clone(), change.text);
+                    self.publish(&document.uri, Some(document.version))?;
+                }
+                Ok(())
+            }
+            DidCloseTextDocument::METHOD => {
+                let Some(params) = extract::<DidCloseTextDocumentParams>(notification) else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                // The problems in a closed document go away with it
+                self.send(Notification::new(
+                    PublishDiagnostics::METHOD.into(),
+                    PublishDiagnosticsParams {
+                        uri,
+                        diagnostics: vec![],
+                        version: None,
+                    },
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn publish(&self, uri: &Uri, version: Option<i32>) -> Result<(), String> {
+        let Some(src) = self.documents.get(uri) else {
+            return Ok(());
+        };
+
+        let state = match file_path(uri) {
+            Some(path) => self.validator.validate_source(path, src),
+            None => self.validator.validate_str(src),
+        };
+        let source_map = SourceMap::new(src);
+        let lines: Vec<&str> = src.split('\n').collect();
+        let diagnostics = state
+            .errors
+            .iter()
+            .map(|error| diagnostic(error, &source_map, &lines))
+            .collect();
+
+        self.send(Notification::new(
+            PublishDiagnostics::METHOD.into(),
+            PublishDiagnosticsParams {
+                uri: uri.clone(),
+                diagnostics,
+                version,
+            },
+        ))
+    }
+
+    fn completions(&self, params: &CompletionParams) -> Vec<CompletionItem> {
+        let position = &params.text_document_position;
+        let uri = &position.text_document.uri;
+        let Some(src) = self.documents.get(uri) else {
+            return vec![];
+        };
+        let Some(schema) = self.validator.schema(self.action_type(uri)) else {
+            return vec![];
+        };
+
+        let lines: Vec<&str> = src.split('\n').collect();
+        let line = position.position.line as usize;
+        let text = lines.get(line).unwrap_or(&"");
+        let before: String = text
+            .chars()
+            .take(column(text, position.position.character))
+            .collect();
+
+        let (mut path, rest) = cursor_context(&lines[..line.min(lines.len())], &before);
+        match split_key(rest) {
+            Some((key, _)) => {
+                path.push(key);
+                value_completions(&schema, &path)
+            }
+            None => key_completions(&schema, &path),
+        }
+    }
+
+    fn hover(&self, params: &HoverParams) -> Option<Hover> {
+        let position = &params.text_document_position_params;
+        let uri = &position.text_document.uri;
+        let src = self.documents.get(uri)?;
+        let line = position.position.line as usize;
+        let text = src.split('\n').nth(line).unwrap_or("");
+
+        let explanation = self.validator.explain_position(
+            self.action_type(uri),
+            src,
+            line + 1,
+            column(text, position.position.character) + 1,
+        )?;
+
+        let mut sections = vec![];
+        if let Some(description) = explanation.description {
+            sections.push(description);
+        }
+        if !explanation.allowed_values.is_empty() {
+            let values: Vec<String> = explanation
+                .allowed_values
+                .iter()
+                .map(|value| format!("`{value}`"))
+                .collect();
+            sections.push(format!("Allowed values: {}", values.join(", ")));
+        }
+        if let Some(url) = explanation.docs_url {
+            sections.push(format!("[Documentation]({url})"));
+        }
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: sections.join("\n\n"),
+            }),
+            range: None,
+        })
+    }
+
+    fn action_type(&self, uri: &Uri) -> ActionType {
+        let path = file_path(uri);
+
+        self.validator
+            .action_type_of(path.as_deref().and_then(|path| path.to_str()))
+    }
+
+    fn send(&self, message: impl Into<Message>) -> Result<(), String> {
+        self.connection
+            .sender
+            .send(message.into())
+            .map_err(|e| format!("Unable to send to the language client: {e}"))
+    }
+}
+
+/// The parameters of a notification.  There's no way to tell the client that a notification
+/// was malformed, so that's logged, and the notification is otherwise ignored.
+fn extract<P: serde::de::DeserializeOwned>(notification: Notification) -> Option<P> {
+    let method = notification.method.clone();
+    notification
+        .extract(&method)
+        .map_err(|e| system::console::error(&format!("Invalid {method} notification: {e}")))
+        .ok()
+}
+
+/// Answer a request whose parameters don't make sense, rather than giving up on the client.
+fn invalid_params(id: RequestId, error: ExtractError<Request>) -> Response {
+    Response::new_err(id, ErrorCode::InvalidParams as i32, error.to_string())
+}
+
+/// The file a document is, if it's a file at all, rather than something that hasn't been
+/// saved yet.
+fn file_path(uri: &Uri) -> Option<PathBuf> {
+    if uri.scheme().map(|scheme| scheme.as_str()) != Some("file") {
+        return None;
+    }
+
+    Some(PathBuf::from(
+        uri.path().as_estr().decode().into_string_lossy().as_ref(),
+    ))
+}
+
+fn diagnostic(error: &ValidationError, source_map: &SourceMap, lines: &[&str]) -> Diagnostic {
+    let (line, character) = match location(error) {
+        Some(location) => (location.line - 1, location.column.saturating_sub(1)),
+        None => start_of(error.path(), source_map),
+    };
+    let text = lines.get(line).unwrap_or(&"");
+    let start = utf16_column(text, character);
+    // The rest of the line is as close as the source map gets to the end of a value
+    let end = utf16_column(text.trim_end(), usize::MAX).max(start);
+
+    Diagnostic {
+        range: Range {
+            start: Position::new(line as u32, start),
+            end: Position::new(line as u32, end),
+        },
+        severity: Some(match error.severity() {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Info => DiagnosticSeverity::INFORMATION,
+        }),
+        code: Some(NumberOrString::String(error.code().into())),
+        source: Some("action-validator".into()),
+        message: error.detail().unwrap_or(error.title()).into(),
+        ..Default::default()
+    }
+}
+
+/// Clients count positions in UTF-16 code units, where we count characters, and the two only
+/// agree on lines without characters outside the Basic Multilingual Plane, such as emoji.
+fn utf16_column(text: &str, column: usize) -> u32 {
+    text.chars()
+        .take(column)
+        .map(char::len_utf16)
+        .sum::<usize>() as u32
+}
+
+/// The (0-based) character column of a client's position on a line.
+fn column(text: &str, character: u32) -> usize {
+    let mut units = 0;
+    text.chars()
+        .take_while(|c| {
+            units += c.len_utf16();
+            units <= character as usize
+        })
+        .count()
+}
+
+fn location(error: &ValidationError) -> Option<&ParseErrorLocation> {
+    match error {
+        ValidationError::Parse { location, .. }
+        | ValidationError::ShellCheck { location, .. }
+        | ValidationError::Suppression { location, .. } => location.as_ref(),
+        _ => None,
+    }
+}
+
+/// Where the value at `path` starts, as a (0-based) line and column, going by its key if it
+/// has one.  Values that aren't in the document, such as a required key that's missing, are
+/// reported where their closest ancestor is.
+fn start_of(mut path: &str, source_map: &SourceMap) -> (usize, usize) {
+    loop {
+        let position = source_map
+            .key_position(path)
+            .or_else(|| source_map.position(path));
+        if let Some(position) = position {
+            return (position.line - 1, position.column);
+        }
+        match path.rfind('/') {
+            Some(end) => path = &path[..end],
+            None => return (0, 0),
+        }
+    }
+}
+
+/// The keys (and array indices) leading to the mapping that the cursor is in, and what's on
+/// its line before it.  Documents being edited are often not valid YAML, so this goes by
+/// indentation alone.
+fn cursor_context<'a>(lines_above: &[&str], before: &'a str) -> (Vec<String>, &'a str) {
+    let content = before.trim_start();
+    let indent = before.len() - content.len();
+
+    // A `-` starts a new item in a sequence, with anything after it inside the item
+    match strip_dash(content) {
+        Some(rest) => {
+            let mut path = parent_path(lines_above, indent);
+            path.push("0".into());
+            (path, rest)
+        }
+        None => (parent_path(lines_above, indent), content),
+    }
+}
+
+fn parent_path(lines_above: &[&str], mut indent: usize) -> Vec<String> {
+    let mut path = vec![];
+
+    for line in lines_above.iter().rev() {
+        if indent == 0 {
+            break;
+        }
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+        let line_indent = line.len() - content.len();
+        if line_indent >= indent {
+            continue;
+        }
+
+        match strip_dash(content) {
+            Some(rest) => {
+                // Either this item's mapping continues on the cursor's line, or the cursor is
+                // under one of its keys
+                let key_indent = line_indent + content.len() - rest.len();
+                if key_indent < indent {
+                    if let Some((key, _)) = split_key(rest) {
+                        path.push(key);
+                    }
+                }
+                path.push("0".into());
+            }
+            None => {
+                if let Some((key, _)) = split_key(content) {
+                    path.push(key);
+                }
+            }
+        }
+        indent = line_indent;
+    }
+
+    path.reverse();
+    path
+}
+
+fn strip_dash(content: &str) -> Option<&str> {
+    match content.strip_prefix('-') {
+        Some(rest) if rest.is_empty() || rest.starts_with(' ') => Some(rest.trim_start()),
+        _ => None,
+    }
+}
+
+/// Split a `key: value` line (where the value may be missing) into its key and value.
+fn split_key(content: &str) -> Option<(String, &str)> {
+    let end = content
+        .find(": ")
+        .or_else(|| content.strip_suffix(':').map(str::len))?;
+    let key = content[..end].trim().trim_matches(['"', '\'']);
+
+    (!key.is_empty()).then(|| (key.to_string(), content[end + 1..].trim_start()))
+}
+
+fn key_completions(schema: &Value, path: &[String]) -> Vec<CompletionItem> {
+    let path: Vec<&str> = path.iter().map(String::as_str).collect();
+    let mut items: Vec<CompletionItem> = vec![];
+
+    for candidate in schemas::at_path(schema, &path) {
+        for key in candidate["properties"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|p| p.0)
+        {
+            if items.iter().any(|item| item.label == *key) {
+                continue;
+            }
+            let key_path: Vec<&str> = path.iter().copied().chain([key.as_str()]).collect();
+            let description = schemas::description(&schemas::at_path(schema, &key_path));
+
+            items.push(CompletionItem {
+                label: key.clone(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                documentation: description.map(|d| Documentation::String(d.into())),
+                ..Default::default()
+            });
+        }
+    }
+
+    items
+}
+
+fn value_completions(schema: &Value, path: &[String]) -> Vec<CompletionItem> {
+    let path: Vec<&str> = path.iter().map(String::as_str).collect();
+
+    schemas::allowed_values(&schemas::at_path(schema, &path))
+        .into_iter()
+        .map(|value| CompletionItem {
+            label: value,
+            kind: Some(CompletionItemKind::ENUM_MEMBER),
+            ..Default::default()
+        })
+        .collect()
+}
@@ -0,0 +1,321 @@
+use serde_json::{Map, Value};
+
+use crate::{
+    expressions::{for_each_expression, references},
+    system,
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// GitHub refuses to run a matrix that generates more jobs than this.
+const MAX_JOBS: usize = 256;
+
+/// Matrices bigger than this (before exclusions) aren't expanded, because doing so would take
+/// forever, and they're well past the job limit anyway.
+const MAX_EXPANSION: usize = 65_536;
+
+/// A single combination of matrix values, as would be presented to a job as `matrix`.
+pub type Combination = Map<String, Value>;
+
+/// The outcome of expanding a matrix.
+pub enum Expansion {
+    /// The matrix was fully expanded into the listed combinations.
+    Combinations(Vec<Combination>),
+    /// The matrix is too big to expand; the count is the number of combinations before
+    /// any exclusions were applied.
+    TooLarge(usize),
+    /// Part of the matrix is determined by an expression, so it cannot be expanded until the
+    /// workflow is run.
+    Dynamic,
+}
+
+/// Work out the concrete combinations that a job's `strategy.matrix` will run with, following
+/// GitHub's rules for `include` and `exclude`.
+pub fn expand(matrix: &Value) -> Expansion {
+    let Some(matrix) = matrix.as_object() else {
+        return Expansion::Dynamic;
+    };
+
+    let mut axes = vec![];
+    for (key, values) in matrix {
+        if key == "include" || key == "exclude" {
+            continue;
+        }
+        match values.as_array() {
+            Some(values) => axes.push((key, values)),
+            None => return Expansion::Dynamic,
+        }
+    }
+
+    let product = axes
+        .iter()
+        .try_fold(1usize, |n, (_, values)| n.checked_mul(values.len()))
+        .unwrap_or(usize::MAX);
+    if product > MAX_EXPANSION {
+        return Expansion::TooLarge(product);
+    }
+
+    // A matrix with no axes of its own is made up entirely of included combinations
+    let mut combinations: Vec<Combination> = if axes.is_empty() {
+        vec![]
+    } else {
+        vec![Map::new()]
+    };
+    for (key, values) in &axes {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.insert(key.to_string(), value.clone());
+                    combination
+                })
+            })
+            .collect();
+    }
+
+    match &matrix.get("exclude") {
+        None => (),
+        Some(Value::Array(excludes)) => {
+            combinations.retain(|combination| {
+                !excludes.iter().any(|exclude| {
+                    exclude.as_object().is_some_and(|exclude| {
+                        exclude
+                            .iter()
+                            .all(|(key, value)| combination.get(key) == Some(value))
+                    })
+                })
+            });
+        }
+        Some(_) => return Expansion::Dynamic,
+    }
+
+    match &matrix.get("include") {
+        None => (),
+        Some(Value::Array(includes)) => {
+            let original_count = combinations.len();
+
+            for include in includes {
+                let Some(include) = include.as_object() else {
+                    return Expansion::Dynamic;
+                };
+
+                // Included values may only be merged into combinations from the original matrix,
+                // and never overwrite a value that came from the original matrix
+                let mut merged = false;
+                for combination in combinations[..original_count].iter_mut() {
+                    let compatible = include.iter().all(|(key, value)| {
+                        !axes.iter().any(|(axis, _)| *axis == key)
+                            || combination.get(key) == Some(value)
+                    });
+
+                    if compatible {
+                        for (key, value) in include {
+                            combination.insert(key.clone(), value.clone());
+                        }
+                        merged = true;
+                    }
+                }
+
+                if !merged {
+                    combinations.push(include.clone());
+                }
+            }
+        }
+        Some(_) => return Expansion::Dynamic,
+    }
+
+    Expansion::Combinations(combinations)
+}
+
+pub fn validate_matrices(doc: &Value, verbose: bool, state: &mut ValidationState) {
+    let Some(jobs) = doc["jobs"].as_object() else {
+        return;
+    };
+
+    for (job_name, job) in jobs {
+        let matrix = &job["strategy"]["matrix"];
+        let matrix_path = format!("/jobs/{job_name}/strategy/matrix");
+
+        let keys = if matrix.is_null() {
+            Some(vec![])
+        } else {
+            validate_matrix(matrix, &matrix_path, job_name, verbose, state)
+        };
+
+        if let (Some(keys), Some(job)) = (keys, job.as_object()) {
+            validate_matrix_references(job, job_name, matrix.is_null(), &keys, state);
+        }
+    }
+}
+
+/// Check a single matrix for problems, returning the keys that will be available to the job in
+/// the `matrix` context (or `None` if they cannot be determined).
+fn validate_matrix(
+    matrix: &Value,
+    path: &str,
+    job_name: &str,
+    verbose: bool,
+    state: &mut ValidationState,
+) -> Option<Vec<String>> {
+    let map = matrix.as_object()?;
+
+    let axes: Vec<&String> = map
+        .keys()
+        .filter(|key| *key != "include" && *key != "exclude")
+        .collect();
+
+    let mut has_empty_axis = false;
+    for axis in &axes {
+        if map[*axis]
+            .as_array()
+            .is_some_and(|values| values.is_empty())
+        {
+            has_empty_axis = true;
+            state.errors.push(ValidationError::InvalidMatrix {
+                code: "matrix_empty_axis".into(),
+                severity: Severity::Error,
+                path: format!("{path}/{axis}"),
+                title: "Empty matrix axis".into(),
+                detail: Some(format!(
+                    "Matrix key {axis} in job {job_name} has no values, so the job will never run"
+                )),
+            });
+        }
+    }
+
+    if let Some(excludes) = map.get("exclude").and_then(Value::as_array) {
+        for (i, exclude) in excludes.iter().enumerate() {
+            for key in exclude.as_object().into_iter().flat_map(Map::keys) {
+                if !axes.contains(&key) {
+                    state.errors.push(ValidationError::InvalidMatrix {
+                        code: "matrix_unknown_exclude_key".into(),
+                        severity: Severity::Error,
+                        path: format!("{path}/exclude/{i}"),
+                        title: "Unknown matrix key in exclude".into(),
+                        detail: Some(format!(
+                            "Exclusion in job {job_name} refers to matrix key {key}, which is not defined in the matrix"
+                        )),
+                    });
+                }
+            }
+        }
+    }
+
+    match expand(matrix) {
+        Expansion::Combinations(combinations) => {
+            if verbose {
+                system::console::log(&format!(
+                    "Matrix for job {job_name} expands to {} jobs",
+                    combinations.len()
+                ));
+            }
+
+            // An empty axis has already been reported as the reason for there being no jobs
+            if combinations.is_empty() && !has_empty_axis {
+                state.errors.push(ValidationError::InvalidMatrix {
+                    code: "matrix_no_jobs".into(),
+                    severity: Severity::Error,
+                    path: path.into(),
+                    title: "Matrix generates no jobs".into(),
+                    detail: Some(format!(
+                        "Matrix for job {job_name} does not generate any job combinations"
+                    )),
+                });
+            } else if combinations.len() > MAX_JOBS {
+                state.errors.push(ValidationError::InvalidMatrix {
+                    code: "matrix_too_large".into(),
+                    severity: Severity::Error,
+                    path: path.into(),
+                    title: "Matrix generates too many jobs".into(),
+                    detail: Some(format!(
+                        "Matrix for job {job_name} expands to {} jobs, but GitHub allows at most {MAX_JOBS}",
+                        combinations.len()
+                    )),
+                });
+            }
+
+            let mut keys: Vec<String> = combinations
+                .iter()
+                .flat_map(|combination| combination.keys().cloned())
+                .collect();
+            keys.sort();
+            keys.dedup();
+            Some(keys)
+        }
+        Expansion::TooLarge(count) => {
+            state.errors.push(ValidationError::InvalidMatrix {
+                code: "matrix_too_large".into(),
+                severity: Severity::Error,
+                path: path.into(),
+                title: "Matrix generates too many jobs".into(),
+                detail: Some(format!(
+                    "Matrix for job {job_name} has {count} combinations before exclusions, but GitHub allows at most {MAX_JOBS} jobs"
+                )),
+            });
+            None
+        }
+        Expansion::Dynamic => None,
+    }
+}
+
+fn validate_matrix_references(
+    job: &Map<String, Value>,
+    job_name: &str,
+    no_matrix: bool,
+    keys: &[String],
+    state: &mut ValidationState,
+) {
+    for (key, value) in job {
+        // The matrix can't refer to itself
+        if key == "strategy" {
+            continue;
+        }
+
+        let mut check = |path: &str, expression: &str| {
+            for reference in references(expression) {
+                // Property access in expressions ignores case, so `Matrix.OS` reads `os`
+                let mut parts = reference.split('.');
+                if !parts
+                    .next()
+                    .is_some_and(|c| c.eq_ignore_ascii_case("matrix"))
+                {
+                    continue;
+                }
+                let Some(matrix_key) = parts.next() else {
+                    continue;
+                };
+
+                if matrix_key != "*" && !keys.iter().any(|key| key.eq_ignore_ascii_case(matrix_key))
+                {
+                    let detail = if no_matrix {
+                        format!(
+                            "matrix.{matrix_key} is used in job {job_name}, which has no matrix"
+                        )
+                    } else {
+                        format!(
+                            "matrix.{matrix_key} is not defined in the matrix for job {job_name}"
+                        )
+                    };
+
+                    state.errors.push(ValidationError::UnresolvedMatrixKey {
+                        code: "unresolved_matrix_key".into(),
+                        severity: Severity::Error,
+                        path: path.into(),
+                        title: "Unresolved matrix key".into(),
+                        detail: Some(detail),
+                    });
+                }
+            }
+        };
+
+        if key == "if" {
+            if let Some(condition) = value.as_str().filter(|c| !c.contains("${{")) {
+                check(&format!("/jobs/{job_name}/if"), condition);
+                continue;
+            }
+        }
+
+        for_each_expression(value, &format!("/jobs/{job_name}/{key}"), &mut check);
+    }
+}
@@ -0,0 +1,288 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::{
+    expressions::{for_each_expression, references},
+    schemas,
+    utils::closest_match,
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// The scopes that can be granted to the `GITHUB_TOKEN`.
+const SCOPES: &[&str] = &[
+    "actions",
+    "artifact-metadata",
+    "attestations",
+    "checks",
+    "contents",
+    "deployments",
+    "discussions",
+    "id-token",
+    "issues",
+    "models",
+    "packages",
+    "pages",
+    "pull-requests",
+    "repository-projects",
+    "security-events",
+    "statuses",
+];
+
+/// Actions whose use of the `GITHUB_TOKEN` is well known, along with the scopes they need.
+const KNOWN_ACTIONS: &[(&str, &[(&str, Access)])] = &[
+    ("actions/checkout", &[("contents", Access::Read)]),
+    ("actions/cache", &[]),
+    ("actions/download-artifact", &[]),
+    ("actions/setup-dotnet", &[]),
+    ("actions/setup-go", &[]),
+    ("actions/setup-java", &[]),
+    ("actions/setup-node", &[]),
+    ("actions/setup-python", &[]),
+    ("actions/upload-artifact", &[]),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Access {
+    None,
+    Read,
+    Write,
+}
+
+impl Access {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "none" => Some(Access::None),
+            "read" => Some(Access::Read),
+            "write" => Some(Access::Write),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Access::None => "none",
+            Access::Read => "read",
+            Access::Write => "write",
+        }
+    }
+}
+
+/// The access needed to each scope by part of a workflow.
+type Needs = BTreeMap<String, Access>;
+
+fn add_needs(needs: &mut Needs, scope: &str, access: Access) {
+    let needed = needs.entry(scope.to_string()).or_insert(Access::None);
+    *needed = (*needed).max(access);
+}
+
+pub fn validate_permissions(doc: &Value, state: &mut ValidationState) {
+    let jobs = doc["jobs"].as_object();
+    let workflow_permissions = &doc["permissions"];
+
+    if workflow_permissions.is_null() && !is_reusable_only(&doc["on"]) {
+        let jobs_without_permissions: Vec<&String> = jobs
+            .into_iter()
+            .flatten()
+            .filter(|(_, job)| job["permissions"].is_null())
+            .map(|(job_name, _)| job_name)
+            .collect();
+
+        if !jobs_without_permissions.is_empty() {
+            state.errors.push(ValidationError::Permissions {
+                code: "missing_permissions".into(),
+                severity: Severity::Warning,
+                path: "/permissions".into(),
+                title: "Missing permissions".into(),
+                detail: Some(format!(
+                    "Workflow does not set permissions, so the default GITHUB_TOKEN permissions apply to jobs {}",
+                    jobs_without_permissions
+                        .iter()
+                        .map(|job_name| job_name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            });
+        }
+    }
+
+    validate_permissions_block(workflow_permissions, "/permissions", state);
+
+    let Some(jobs) = jobs else {
+        return;
+    };
+
+    for (job_name, job) in jobs {
+        let path = format!("/jobs/{job_name}/permissions");
+        validate_permissions_block(&job["permissions"], &path, state);
+
+        if let Some(permissions) = job["permissions"].as_object() {
+            if let Some(needed) = needed_access(job, &doc["env"]) {
+                let users = format!("job {job_name}");
+                validate_needed(permissions, &needed, &path, &users, state);
+            }
+        }
+    }
+
+    // Workflow-level permissions are only known to be unneeded if every job that inherits them
+    // is known not to need them
+    if let Some(permissions) = workflow_permissions.as_object() {
+        let inheriting: Vec<&Value> = jobs
+            .values()
+            .filter(|job| job["permissions"].is_null())
+            .collect();
+
+        let needed = inheriting
+            .iter()
+            .map(|job| needed_access(job, &doc["env"]))
+            .try_fold(Needs::new(), |mut all_needs, needs| {
+                for (scope, access) in needs? {
+                    add_needs(&mut all_needs, &scope, access);
+                }
+                Some(all_needs)
+            });
+
+        if let Some(needed) = needed.filter(|_| !inheriting.is_empty()) {
+            validate_needed(
+                permissions,
+                &needed,
+                "/permissions",
+                "the jobs without their own permissions",
+                state,
+            );
+        }
+    }
+}
+
+/// A workflow that can only be called from other workflows gets its permissions from the caller.
+fn is_reusable_only(on: &Value) -> bool {
+    match on {
+        Value::String(event) => event == "workflow_call",
+        Value::Array(events) => events.iter().all(|event| event == "workflow_call"),
+        Value::Object(events) => events.keys().all(|event| event == "workflow_call"),
+        _ => false,
+    }
+}
+
+fn validate_permissions_block(permissions: &Value, path: &str, state: &mut ValidationState) {
+    match permissions {
+        Value::String(permissions) if permissions == "write-all" => {
+            state.errors.push(ValidationError::Permissions {
+                code: "write_all_permissions".into(),
+                severity: Severity::Error,
+                path: path.into(),
+                title: "Write access to all scopes".into(),
+                detail: Some(
+                    "permissions: write-all grants the GITHUB_TOKEN write access to every scope; grant only the scopes that are needed instead".into(),
+                ),
+            });
+        }
+        Value::Object(permissions) => {
+            for scope in permissions.keys() {
+                if !SCOPES.contains(&scope.as_str()) {
+                    let suggestion = closest_match(scope, SCOPES)
+                        .map(|known| format!("; did you mean {known}?"))
+                        .unwrap_or_default();
+
+                    state.errors.push(ValidationError::Permissions {
+                        code: "unknown_permission_scope".into(),
+                        severity: Severity::Error,
+                        path: format!("{path}/{scope}"),
+                        title: "Unknown permission scope".into(),
+                        detail: Some(format!("{scope} is not a GITHUB_TOKEN scope{suggestion}")),
+                    });
+
+                    // The schema doesn't allow unknown scopes either, but can't suggest another
+                    let schema_detail = format!("Additional property '{scope}' is not allowed");
+                    schemas::remove_covered(&mut state.errors, &|error| {
+                        error.code() == "properties"
+                            && error.path() == path
+                            && error.detail() == Some(schema_detail.as_str())
+                    });
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn validate_needed(
+    permissions: &Map<String, Value>,
+    needed: &Needs,
+    path: &str,
+    users: &str,
+    state: &mut ValidationState,
+) {
+    for (scope, access) in permissions {
+        let Some(access) = access.as_str().and_then(Access::parse) else {
+            continue;
+        };
+        let needed = needed.get(scope).copied().unwrap_or(Access::None);
+
+        if access > needed && SCOPES.contains(&scope.as_str()) {
+            state.errors.push(ValidationError::Permissions {
+                code: "unneeded_permission".into(),
+                severity: Severity::Warning,
+                path: format!("{path}/{scope}"),
+                title: "Unneeded permission".into(),
+                detail: Some(format!(
+                    "{scope}: {} is granted, but nothing in {users} needs more than {scope}: {}",
+                    access.name(),
+                    needed.name()
+                )),
+            });
+        }
+    }
+}
+
+/// Work out which scopes a job needs, if that can be determined.  It can only be determined if
+/// the job never passes the token to anything, and every action it uses is one whose needs are
+/// known.
+fn needed_access(job: &Value, workflow_env: &Value) -> Option<Needs> {
+    // Calls to reusable workflows can do anything at all
+    if !job["uses"].is_null() {
+        return None;
+    }
+
+    let mut uses_token = false;
+    let mut check = |_: &str, expression: &str| {
+        uses_token |= references(expression)
+            .iter()
+            .any(|reference| reference == "github.token" || reference == "secrets.GITHUB_TOKEN");
+    };
+    for_each_expression(job, "", &mut check);
+    for_each_expression(workflow_env, "", &mut check);
+
+    if uses_token {
+        return None;
+    }
+
+    let steps = job["steps"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let runs_scripts = steps.iter().any(|step| !step["run"].is_null());
+
+    let mut needed = Needs::new();
+    for step in steps {
+        let Some(uses) = step["uses"].as_str() else {
+            continue;
+        };
+        let action = uses.split('@').next().unwrap_or_default();
+
+        let (_, scopes) = KNOWN_ACTIONS.iter().find(|(known, _)| *known == action)?;
+        for (scope, access) in scopes.iter() {
+            add_needs(&mut needed, scope, *access);
+        }
+
+        // The checkout action leaves the token in the git config for later steps to use, so
+        // a script could be pushing with it
+        let persists_credentials = step["with"]["persist-credentials"] != false
+            && step["with"]["persist-credentials"] != "false";
+        if action == "actions/checkout" && persists_credentials && runs_scripts {
+            add_needs(&mut needed, "contents", Access::Write);
+        }
+    }
+
+    Some(needed)
+}
@@ -0,0 +1,155 @@
+use serde_json::Value;
+
+use crate::{
+    expressions::{expressions, references},
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// Events which run workflows with a privileged token and access to secrets, even when they
+/// are triggered by a pull request from a fork.
+const PRIVILEGED_EVENTS: &[&str] = &["pull_request_target", "workflow_run"];
+
+/// Context values which identify the (untrusted) code in a pull request.
+const PULL_REQUEST_HEAD_CONTEXTS: &[&str] = &[
+    "github.head_ref",
+    "github.event.number",
+    "github.event.pull_request.number",
+    "github.event.pull_request.head.sha",
+    "github.event.pull_request.head.ref",
+    "github.event.pull_request.head.repo.full_name",
+    "github.event.pull_request.merge_commit_sha",
+    "github.event.workflow_run.head_sha",
+    "github.event.workflow_run.head_branch",
+    "github.event.workflow_run.head_repository.full_name",
+    "github.event.workflow_run.pull_requests.*.head.sha",
+    "github.event.workflow_run.pull_requests.*.head.ref",
+    "github.event.workflow_run.pull_requests.*.number",
+];
+
+pub fn validate_privileged_checkouts(doc: &Value, state: &mut ValidationState) {
+    let triggers: Vec<&str> = match &doc["on"] {
+        Value::String(event) => vec![event.as_str()],
+        Value::Array(events) => events.iter().filter_map(Value::as_str).collect(),
+        Value::Object(events) => events.keys().map(String::as_str).collect(),
+        _ => vec![],
+    };
+    let Some(trigger) = triggers
+        .into_iter()
+        .find(|trigger| PRIVILEGED_EVENTS.contains(trigger))
+    else {
+        return;
+    };
+
+    let Some(jobs) = doc["jobs"].as_object() else {
+        return;
+    };
+
+    for (job_name, job) in jobs {
+        let Some(steps) = job["steps"].as_array() else {
+            continue;
+        };
+
+        let Some((checkout_index, head)) = steps
+            .iter()
+            .enumerate()
+            .find_map(|(i, step)| checks_out_head(step).map(|head| (i, head)))
+        else {
+            continue;
+        };
+
+        let path = format!("/jobs/{job_name}/steps/{checkout_index}");
+        let mut chain = vec![format!(
+            "{path}: {} checks out {head}",
+            describe(&steps[checkout_index])
+        )];
+
+        for (i, step) in steps.iter().enumerate().skip(checkout_index + 1) {
+            if runs_checked_out_code(step) {
+                chain.push(format!(
+                    "/jobs/{job_name}/steps/{i}: {} runs the checked out code",
+                    describe(step)
+                ));
+            }
+        }
+
+        if chain.len() > 1 {
+            state.errors.push(ValidationError::PrivilegedCheckout {
+                code: "privileged_checkout".into(),
+                severity: Severity::Error,
+                path,
+                title: "Untrusted code run in privileged workflow".into(),
+                detail: Some(format!(
+                    "Job {job_name} runs in response to {trigger}, with access to secrets and a privileged GITHUB_TOKEN, but checks out and runs code from the pull request; anyone who can open a pull request can take control of the job"
+                )),
+                steps: chain,
+            });
+        }
+    }
+}
+
+/// If the step checks out the head of a pull request, return the context used to do so.
+fn checks_out_head(step: &Value) -> Option<String> {
+    let head_reference = |s: &str| {
+        expressions(s)
+            .into_iter()
+            .flat_map(references)
+            .find(|reference| is_head_context(reference))
+    };
+
+    if let Some(uses) = step["uses"].as_str() {
+        if uses.starts_with("actions/checkout@") {
+            let with = &step["with"];
+            return [&with["ref"], &with["repository"]]
+                .into_iter()
+                .filter_map(Value::as_str)
+                .find_map(head_reference);
+        }
+    }
+
+    let script = step["run"].as_str()?;
+    let fetches = ["gh pr checkout", "git checkout", "git fetch", "git switch"]
+        .iter()
+        .any(|command| script.contains(command));
+
+    if fetches {
+        head_reference(script)
+    } else {
+        None
+    }
+}
+
+fn is_head_context(reference: &str) -> bool {
+    PULL_REQUEST_HEAD_CONTEXTS.iter().any(|context| {
+        let pattern: Vec<&str> = context.split('.').collect();
+        let reference: Vec<&str> = reference.split('.').collect();
+
+        pattern.len() == reference.len()
+            && pattern
+                .iter()
+                .zip(&reference)
+                .all(|(p, r)| p == r || *p == "*")
+    })
+}
+
+/// Scripts and local actions run whatever is in the workspace, which is now the pull request.
+fn runs_checked_out_code(step: &Value) -> bool {
+    !step["run"].is_null()
+        || step["uses"]
+            .as_str()
+            .is_some_and(|uses| uses.starts_with("./"))
+}
+
+fn describe(step: &Value) -> String {
+    if let Some(name) = step["name"].as_str() {
+        format!("step {name:?}")
+    } else if let Some(uses) = step["uses"].as_str() {
+        format!("step using {uses}")
+    } else {
+        let command = step["run"]
+            .as_str()
+            .and_then(|script| script.lines().next())
+            .unwrap_or_default();
+        format!("step running {command:?}")
+    }
+}
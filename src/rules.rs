@@ -0,0 +1,348 @@
+use serde_json::Value;
+use std::{fmt, path::Path};
+
+use crate::{
+    action_runs, composite,
+    config::{ActionType, RunConfig},
+    dependabot, injection, issue_forms, matrix, permissions, privileged_checkout, runners,
+    shellcheck,
+    source_map::SourceMap,
+    uses,
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+    workflow_commands,
+};
+
+/// A check that is run on every file, after it has been validated against its schema.
+///
+/// Every check `action-validator` makes beyond the schema is a rule, and more can be added to
+/// a [`Validator`](crate::Validator) with [`Validator::rule`](crate::Validator::rule):
+///
+/// ```
+/// use action_validator::{ActionType, Rule, RuleContext, Severity, Validator};
+///
+/// struct JobTimeouts;
+///
+/// impl Rule for JobTimeouts {
+///     fn check(&self, context: &mut RuleContext) {
+///         if context.action_type() != ActionType::Workflow {
+///             return;
+///         }
+///         let Some(jobs) = context.doc()["jobs"].as_object() else {
+///             return;
+///         };
+///
+///         for (name, job) in jobs {
+///             if job["timeout-minutes"].is_null() {
+///                 context.report(
+///                     Severity::Error,
+///                     "missing_timeout",
+///                     &format!("/jobs/{name}"),
+///                     "Job has no timeout",
+///                     format!("Job {name} doesn't set timeout-minutes"),
+///                 );
+///             }
+///         }
+///     }
+/// }
+///
+/// let validator = Validator::new().rule(JobTimeouts);
+/// let state = validator.validate_str(
+///     "on: push
+/// permissions: {}
+/// jobs:
+///   test:
+///     runs-on: ubuntu-latest
+///     steps:
+///       - run: make test
+/// ",
+/// );
+/// assert_eq!(state.errors[0].code(), "missing_timeout");
+/// ```
+///
+/// What rules report is subject to the same levels and suppression comments as everything
+/// else.
+pub trait Rule: Send + Sync {
+    /// Check the document in `context`, reporting any problems found.
+    fn check(&self, context: &mut RuleContext);
+}
+
+impl fmt::Debug for dyn Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rule").finish_non_exhaustive()
+    }
+}
+
+/// What a [`Rule`] is checking, and where it reports what it finds.
+pub struct RuleContext<'a> {
+    pub(crate) doc: &'a Value,
+    pub(crate) config: &'a RunConfig<'a>,
+    pub(crate) source_map: &'a SourceMap,
+    pub(crate) state: &'a mut ValidationState,
+}
+
+impl<'a> RuleContext<'a> {
+    /// The document being checked.
+    pub fn doc(&self) -> &'a Value {
+        self.doc
+    }
+
+    pub fn action_type(&self) -> ActionType {
+        self.config.action_type
+    }
+
+    /// The path of the file being checked, if it came from a file.
+    pub fn file_path(&self) -> Option<&str> {
+        self.config.file_path
+    }
+
+    /// Report a problem with the node at `path`, a JSON pointer such as `/jobs/build`.
+    pub fn report(
+        &mut self,
+        severity: Severity,
+        code: &str,
+        path: &str,
+        title: &str,
+        detail: impl Into<String>,
+    ) {
+        self.state.errors.push(ValidationError::Custom {
+            code: code.into(),
+            severity,
+            path: path.into(),
+            title: title.into(),
+            detail: Some(detail.into()),
+        });
+    }
+
+    fn is_workflow(&self) -> bool {
+        matches!(self.config.action_type, ActionType::Workflow)
+    }
+
+    fn is_action(&self) -> bool {
+        matches!(self.config.action_type, ActionType::Action)
+    }
+
+    /// Whether the file is one that has jobs and steps, rather than some other kind of file.
+    fn has_steps(&self) -> bool {
+        self.is_workflow() || self.is_action()
+    }
+}
+
+/// The rules that are always run, in the order their findings are reported.
+pub(crate) const BUILTIN: &[&dyn Rule] = &[
+    &ActionRuns,
+    &CompositeIo,
+    &PathGlobs,
+    &JobNeeds,
+    &Matrices,
+    &Runners,
+    &Permissions,
+    &ScriptInjection,
+    &WorkflowCommands,
+    &ShellCheck,
+    &PrivilegedCheckout,
+    &Uses,
+    &Pinning,
+    &Dependabot,
+    &IssueForms,
+];
+
+struct ActionRuns;
+
+impl Rule for ActionRuns {
+    fn check(&self, context: &mut RuleContext) {
+        if !context.is_action() {
+            return;
+        }
+        // Files that the action refers to can only be checked if we know where it is
+        let action_dir = context
+            .config
+            .file_path
+            .map(|path| match Path::new(path).parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            });
+        action_runs::validate_runs(context.doc, action_dir, context.state);
+    }
+}
+
+struct CompositeIo;
+
+impl Rule for CompositeIo {
+    fn check(&self, context: &mut RuleContext) {
+        if context.is_action() {
+            composite::validate_composite_io(context.doc, context.state);
+        }
+    }
+}
+
+struct PathGlobs;
+
+impl Rule for PathGlobs {
+    fn check(&self, context: &mut RuleContext) {
+        if context.is_workflow() {
+            crate::validate_paths(context.doc, context.config.rootdir.as_ref(), context.state);
+        }
+    }
+}
+
+struct JobNeeds;
+
+impl Rule for JobNeeds {
+    fn check(&self, context: &mut RuleContext) {
+        if context.is_workflow() {
+            crate::validate_job_needs(context.doc, context.state);
+        }
+    }
+}
+
+struct Matrices;
+
+impl Rule for Matrices {
+    fn check(&self, context: &mut RuleContext) {
+        if context.is_workflow() {
+            matrix::validate_matrices(context.doc, context.config.verbose, context.state);
+        }
+    }
+}
+
+struct Runners;
+
+impl Rule for Runners {
+    fn check(&self, context: &mut RuleContext) {
+        if context.is_workflow() {
+            runners::validate_runners(
+                context.doc,
+                context.config.runner_labels,
+                context.config.runner_groups,
+                context.state,
+            );
+        }
+    }
+}
+
+struct Permissions;
+
+impl Rule for Permissions {
+    fn check(&self, context: &mut RuleContext) {
+        if context.is_workflow() {
+            permissions::validate_permissions(context.doc, context.state);
+        }
+    }
+}
+
+struct ScriptInjection;
+
+impl Rule for ScriptInjection {
+    fn check(&self, context: &mut RuleContext) {
+        if context.is_workflow() {
+            injection::validate_workflow_scripts(context.doc, context.state);
+        } else if context.is_action() {
+            injection::validate_action_scripts(context.doc, context.state);
+        }
+    }
+}
+
+struct WorkflowCommands;
+
+impl Rule for WorkflowCommands {
+    fn check(&self, context: &mut RuleContext) {
+        if context.is_workflow() {
+            workflow_commands::validate_workflow_commands(context.doc, context.state);
+        } else if context.is_action() {
+            workflow_commands::validate_action_commands(context.doc, context.state);
+        }
+    }
+}
+
+struct ShellCheck;
+
+impl Rule for ShellCheck {
+    fn check(&self, context: &mut RuleContext) {
+        let Some(program) = context.config.shellcheck else {
+            return;
+        };
+        if context.has_steps() {
+            shellcheck::validate_scripts(
+                context.doc,
+                context.config.action_type,
+                program,
+                context.source_map,
+                context.state,
+            );
+        }
+    }
+}
+
+struct PrivilegedCheckout;
+
+impl Rule for PrivilegedCheckout {
+    fn check(&self, context: &mut RuleContext) {
+        if context.is_workflow() {
+            privileged_checkout::validate_privileged_checkouts(context.doc, context.state);
+        }
+    }
+}
+
+struct Uses;
+
+impl Rule for Uses {
+    fn check(&self, context: &mut RuleContext) {
+        if context.has_steps() {
+            uses::validate_uses(context.doc, context.config.action_type, context.state);
+        }
+    }
+}
+
+struct Pinning;
+
+impl Rule for Pinning {
+    fn check(&self, context: &mut RuleContext) {
+        if context.has_steps() {
+            uses::validate_pinning(
+                context.doc,
+                context.config.action_type,
+                context.config.trusted_owners,
+                context.state,
+            );
+        }
+    }
+}
+
+struct Dependabot;
+
+impl Rule for Dependabot {
+    fn check(&self, context: &mut RuleContext) {
+        if context.config.action_type != ActionType::Dependabot {
+            return;
+        }
+        // The configuration lives in the repository's .github directory
+        let repo_root = match &context.config.rootdir {
+            Some(rootdir) => rootdir.as_path(),
+            None => context
+                .config
+                .file_path
+                .map(Path::new)
+                .and_then(Path::parent)
+                .filter(|dir| dir.file_name().is_some_and(|name| name == ".github"))
+                .and_then(Path::parent)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new(".")),
+        };
+        dependabot::validate_dependabot(context.doc, repo_root, context.state);
+    }
+}
+
+struct IssueForms;
+
+impl Rule for IssueForms {
+    fn check(&self, context: &mut RuleContext) {
+        match context.config.action_type {
+            ActionType::IssueForm => issue_forms::validate_issue_form(context.doc, context.state),
+            ActionType::IssueConfig => {
+                issue_forms::validate_issue_config(context.doc, context.state)
+            }
+            _ => {}
+        }
+    }
+}
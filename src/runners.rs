@@ -0,0 +1,247 @@
+use serde_json::Value;
+
+use crate::{
+    expressions::expressions,
+    matrix::{self, Expansion},
+    utils::closest_match,
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+// These lists reflect the runner images GitHub provides as of October 2026; see
+// https://github.com/actions/runner-images for the current state of play.
+
+/// Labels for the GitHub-hosted runners that can be used.
+const HOSTED_LABELS: &[&str] = &[
+    "ubuntu-latest",
+    "ubuntu-slim",
+    "ubuntu-24.04",
+    "ubuntu-22.04",
+    "ubuntu-24.04-arm",
+    "ubuntu-22.04-arm",
+    "windows-latest",
+    "windows-2025",
+    "windows-2022",
+    "windows-11-arm",
+    "macos-latest",
+    "macos-26",
+    "macos-15",
+    "macos-15-intel",
+    "macos-14",
+    "macos-latest-large",
+    "macos-26-large",
+    "macos-15-large",
+    "macos-14-large",
+    "macos-latest-xlarge",
+    "macos-26-xlarge",
+    "macos-15-xlarge",
+    "macos-14-xlarge",
+];
+
+/// Labels for GitHub-hosted runner images which still work, but are scheduled for removal.
+const DEPRECATED_LABELS: &[&str] = &["macos-14", "macos-14-large", "macos-14-xlarge"];
+
+/// Labels for GitHub-hosted runner images which have been removed; jobs using them will never
+/// start.
+const RETIRED_LABELS: &[&str] = &[
+    "ubuntu-16.04",
+    "ubuntu-18.04",
+    "ubuntu-20.04",
+    "windows-2016",
+    "windows-2019",
+    "macos-10.15",
+    "macos-11",
+    "macos-12",
+    "macos-12-large",
+    "macos-12-xl",
+    "macos-13",
+    "macos-13-large",
+    "macos-13-xlarge",
+];
+
+/// Labels that every self-hosted runner is given automatically.
+const SELF_HOSTED_LABELS: &[&str] = &[
+    "self-hosted",
+    "linux",
+    "windows",
+    "macos",
+    "x64",
+    "arm",
+    "arm64",
+];
+
+pub fn validate_runners(
+    doc: &Value,
+    labels: &[String],
+    groups: &[String],
+    state: &mut ValidationState,
+) {
+    let Some(jobs) = doc["jobs"].as_object() else {
+        return;
+    };
+
+    for (job_name, job) in jobs {
+        let runs_on = &job["runs-on"];
+        let path = format!("/jobs/{job_name}/runs-on");
+
+        match runs_on {
+            Value::String(_) | Value::Array(_) => {
+                validate_labels(runs_on, &path, job, labels, state);
+            }
+            Value::Object(runner) => {
+                // Runner groups are only checked if we've been told which ones there are
+                if let Some(group) = runner.get("group").and_then(Value::as_str) {
+                    if !groups.is_empty()
+                        && expressions(group).is_empty()
+                        && !contains(groups, group)
+                    {
+                        state.errors.push(ValidationError::InvalidRunner {
+                            code: "unknown_runner_group".into(),
+                            severity: Severity::Error,
+                            path: format!("{path}/group"),
+                            title: "Unknown runner group".into(),
+                            detail: Some(format!(
+                                "Job {job_name} requests runner group {group}, which is not a known runner group"
+                            )),
+                        });
+                    }
+                }
+
+                if let Some(runner_labels) = runner.get("labels") {
+                    validate_labels(runner_labels, &format!("{path}/labels"), job, labels, state);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn validate_labels(
+    runs_on: &Value,
+    path: &str,
+    job: &Value,
+    configured: &[String],
+    state: &mut ValidationState,
+) {
+    let requested: Vec<&str> = match runs_on {
+        Value::String(label) => vec![label],
+        Value::Array(labels) => labels.iter().filter_map(Value::as_str).collect(),
+        _ => return,
+    };
+
+    let resolved: Vec<(String, Option<String>)> = requested
+        .into_iter()
+        .flat_map(|label| resolve_label(label, job))
+        .collect();
+
+    // Custom labels on self-hosted runners can only be checked if we've been told what they are
+    let check_custom = !configured.is_empty()
+        || !resolved
+            .iter()
+            .any(|(label, _)| label.eq_ignore_ascii_case("self-hosted"));
+    // Without a list of labels, an unknown label might still belong to a larger runner (such as
+    // `ubuntu-latest-8-cores`), so it's only worth a warning
+    let unknown_severity = if configured.is_empty() {
+        Severity::Warning
+    } else {
+        Severity::Error
+    };
+
+    for (label, source) in resolved {
+        let lower = label.to_lowercase();
+        let source = source
+            .map(|reference| format!(" (from {reference})"))
+            .unwrap_or_default();
+
+        if RETIRED_LABELS.contains(&lower.as_str()) {
+            state.errors.push(ValidationError::InvalidRunner {
+                code: "retired_runner_label".into(),
+                severity: Severity::Error,
+                path: path.into(),
+                title: "Retired runner image".into(),
+                detail: Some(format!(
+                    "GitHub no longer provides the {label} runner image{source}, so the job will never start"
+                )),
+            });
+        } else if DEPRECATED_LABELS.contains(&lower.as_str()) {
+            state.errors.push(ValidationError::InvalidRunner {
+                code: "deprecated_runner_label".into(),
+                severity: Severity::Warning,
+                path: path.into(),
+                title: "Deprecated runner image".into(),
+                detail: Some(format!(
+                    "The {label} runner image{source} is deprecated, and will be removed by GitHub"
+                )),
+            });
+        } else if check_custom
+            && !HOSTED_LABELS.contains(&lower.as_str())
+            && !SELF_HOSTED_LABELS.contains(&lower.as_str())
+            && !contains(configured, &label)
+        {
+            let suggestion = closest_match(&lower, HOSTED_LABELS)
+                .map(|known| format!("; did you mean {known}?"))
+                .unwrap_or_default();
+
+            state.errors.push(ValidationError::InvalidRunner {
+                code: "unknown_runner_label".into(),
+                severity: unknown_severity,
+                path: path.into(),
+                title: "Unknown runner label".into(),
+                detail: Some(format!(
+                    "No known runner has the label {label}{source}{suggestion}"
+                )),
+            });
+        }
+    }
+}
+
+/// Work out the concrete label(s) that a `runs-on` entry refers to, along with the matrix key
+/// they came from, if any.  Labels that depend on expressions other than a plain reference to a
+/// matrix value can't be determined, and are skipped.
+fn resolve_label(label: &str, job: &Value) -> Vec<(String, Option<String>)> {
+    if expressions(label).is_empty() {
+        return vec![(label.to_string(), None)];
+    }
+
+    // Only a label which is nothing but a reference to a matrix value can be resolved
+    let Some(key) = label
+        .trim()
+        .strip_prefix("${{")
+        .and_then(|label| label.strip_suffix("}}"))
+        .and_then(|expression| expression.trim().strip_prefix("matrix."))
+        .filter(|key| {
+            key.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+    else {
+        return vec![];
+    };
+    let reference = format!("matrix.{key}");
+
+    let Expansion::Combinations(combinations) = matrix::expand(&job["strategy"]["matrix"]) else {
+        return vec![];
+    };
+
+    let mut labels: Vec<(String, Option<String>)> = vec![];
+    for value in combinations.iter().filter_map(|c| c.get(key)) {
+        // A matrix value can itself be a list of labels
+        let values = match value {
+            Value::Array(values) => values.iter().filter_map(Value::as_str).collect(),
+            Value::String(value) => vec![value.as_str()],
+            _ => vec![],
+        };
+
+        for value in values {
+            if !labels.iter().any(|(label, _)| label == value) {
+                labels.push((value.to_string(), Some(reference.clone())));
+            }
+        }
+    }
+
+    labels
+}
+
+/// Runner labels and group names are case-insensitive.
+fn contains(list: &[String], item: &str) -> bool {
+    list.iter().any(|entry| entry.eq_ignore_ascii_case(item))
+}
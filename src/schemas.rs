@@ -1,27 +1,255 @@
 use serde_json::Value;
+use std::path::Path;
 
-use crate::validation_state::ValidationState;
+use crate::{
+    config::ActionType, system, validation_error::ValidationError,
+    validation_state::ValidationState,
+};
 
-pub fn validate_as_action(doc: &Value) -> ValidationState {
-    validate_with_schema(
-        doc,
-        include_bytes!("schemastore/src/schemas/json/github-action.json"),
-    )
+pub const ACTION_SCHEMA: &[u8] = include_bytes!("schemastore/src/schemas/json/github-action.json");
+
+pub const WORKFLOW_SCHEMA: &[u8] =
+    include_bytes!("schemastore/src/schemas/json/github-workflow.json");
+
+/// The commit of the schemastore repository that the embedded schemas come from.
+pub const SCHEMASTORE_COMMIT: &str = env!("SCHEMASTORE_COMMIT");
+
+// Schemas for the other types of file, which are only built in when they're available
+include!(concat!(env!("OUT_DIR"), "/optional_schemas.rs"));
+
+/// The built-in schema for a type of file, if there is one.
+pub fn embedded(action_type: ActionType) -> Option<&'static [u8]> {
+    match action_type {
+        ActionType::Action => Some(ACTION_SCHEMA),
+        ActionType::Workflow => Some(WORKFLOW_SCHEMA),
+        ActionType::Dependabot => DEPENDABOT_SCHEMA,
+        ActionType::IssueForm => ISSUE_FORM_SCHEMA,
+        ActionType::IssueConfig => ISSUE_CONFIG_SCHEMA,
+        ActionType::Funding => FUNDING_SCHEMA,
+        ActionType::ReleaseConfig => RELEASE_CONFIG_SCHEMA,
+        ActionType::WorkflowTemplate => WORKFLOW_TEMPLATE_SCHEMA,
+    }
 }
 
-pub fn validate_as_workflow(doc: &Value) -> ValidationState {
-    validate_with_schema(
-        doc,
-        include_bytes!("schemastore/src/schemas/json/github-workflow.json"),
-    )
+/// Validate a document against `schema`, or the built-in schema for its type if none is
+/// given.  Types without a built-in schema are only checked by the rules.
+pub fn validate(doc: &Value, action_type: ActionType, schema: Option<&Value>) -> ValidationState {
+    let schema = match (schema, embedded(action_type)) {
+        (Some(schema), _) => schema.clone(),
+        (None, Some(schema)) => parse_embedded(schema),
+        (None, None) => {
+            return ValidationState {
+                action_type: None,
+                file_path: None,
+                errors: vec![],
+            }
+        }
+    };
+
+    validate_with_schema(doc, schema)
 }
 
-fn validate_with_schema(doc: &Value, schema: &[u8]) -> ValidationState {
-    let schema_json: serde_json::Value =
-        serde_json::from_str(String::from_utf8_lossy(schema).as_ref()).unwrap();
+pub fn parse_embedded(schema: &[u8]) -> Value {
+    serde_json::from_slice(schema).expect("built-in schema to be valid JSON")
+}
 
+fn validate_with_schema(doc: &Value, schema_json: Value) -> ValidationState {
     let mut scope = valico::json_schema::Scope::new();
-    let validator = scope.compile_and_return(schema_json, false).unwrap();
+    let validator = scope
+        .compile_and_return(schema_json, false)
+        .expect("schema to have been checked before it's used");
 
     validator.validate(doc).into()
 }
+
+/// Remove the schema's findings about a problem that a rule has reported more helpfully, so
+/// that each problem is only reported once.  A `oneOf` or `anyOf` which only failed because of
+/// them goes too, since one of its branches would otherwise have matched.  Returns whether
+/// anything was removed.
+pub fn remove_covered(
+    errors: &mut Vec<ValidationError>,
+    covered: &impl Fn(&ValidationError) -> bool,
+) -> bool {
+    let mut removed = false;
+
+    errors.retain_mut(|error| {
+        if covered(error) {
+            removed = true;
+            return false;
+        }
+
+        match error {
+            ValidationError::AnyOf { states, .. } | ValidationError::OneOf { states, .. } => {
+                let mut emptied = false;
+                for state in states {
+                    if remove_covered(&mut state.errors, covered) {
+                        removed = true;
+                        emptied |= state.errors.is_empty();
+                    }
+                }
+                !emptied
+            }
+            _ => true,
+        }
+    });
+
+    removed
+}
+
+/// Read a schema from a JSON or YAML file, and make sure it can be used.
+pub fn load(path: &Path) -> Result<Value, String> {
+    let src = system::fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read schema {}: {e}", path.display()))?;
+
+    let schema = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&src).map_err(|e| e.to_string())
+    } else {
+        yaml_serde::from_str(&src).map_err(|e| e.to_string())
+    };
+    let schema = schema.map_err(|e| format!("Invalid schema {}: {e}", path.display()))?;
+
+    check(&schema).map_err(|e| format!("Invalid schema {}: {e}", path.display()))?;
+
+    Ok(schema)
+}
+
+/// Make sure a schema can be compiled, so that problems with it are reported once, rather
+/// than when validating each file.
+pub fn check(schema: &Value) -> Result<(), String> {
+    let mut scope = valico::json_schema::Scope::new();
+
+    scope
+        .compile_and_return(schema.clone(), false)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// The parts of `schema` that describe the value at `path` in a document, where `path` is the
+/// keys and array indices leading to it.  References are followed, and each branch of a
+/// `oneOf`, `anyOf` or `allOf` is included, since which of them applies can't be known without
+/// the value itself.
+pub fn at_path<'a>(schema: &'a Value, path: &[&str]) -> Vec<&'a Value> {
+    let mut found = vec![];
+    expand(schema, schema, &mut found, 0);
+
+    for segment in path {
+        let mut next = vec![];
+        for candidate in found {
+            for child in children(candidate, segment) {
+                expand(schema, child, &mut next, 0);
+            }
+        }
+        found = next;
+    }
+
+    found
+}
+
+fn children<'a>(schema: &'a Value, segment: &str) -> Vec<&'a Value> {
+    if segment.parse::<usize>().is_ok() {
+        if let Some(items) = schema.get("items").filter(|items| items.is_object()) {
+            return vec![items];
+        }
+    }
+
+    if let Some(property) = schema["properties"].get(segment) {
+        return vec![property];
+    }
+
+    // Checking the patterns would need a regex engine, and the ones in the built-in schemas
+    // are all for names (of jobs, inputs and the like) that any key could be
+    let mut found: Vec<&Value> = schema["patternProperties"]
+        .as_object()
+        .into_iter()
+        .flat_map(|patterns| patterns.values())
+        .collect();
+    if let Some(additional) = schema
+        .get("additionalProperties")
+        .filter(|additional| additional.is_object())
+    {
+        found.push(additional);
+    }
+
+    found
+}
+
+fn expand<'a>(root: &'a Value, schema: &'a Value, found: &mut Vec<&'a Value>, depth: usize) {
+    // Recursive schemas would otherwise never finish expanding
+    if depth > 16 || !schema.is_object() || found.iter().any(|f| std::ptr::eq(*f, schema)) {
+        return;
+    }
+    found.push(schema);
+
+    if let Some(reference) = schema["$ref"].as_str().and_then(|r| r.strip_prefix('#')) {
+        if let Some(target) = root.pointer(reference) {
+            expand(root, target, found, depth + 1);
+        }
+    }
+    for keyword in ["oneOf", "anyOf", "allOf"] {
+        for branch in schema[keyword].as_array().into_iter().flatten() {
+            expand(root, branch, found, depth + 1);
+        }
+    }
+}
+
+/// The first description given by any of the parts of a schema.
+pub fn description<'a>(schemas: &[&'a Value]) -> Option<&'a str> {
+    schemas
+        .iter()
+        .find_map(|schema| schema["description"].as_str())
+}
+
+/// The values that the parts of a schema allow, if they're limited to a list of scalars.
+pub fn allowed_values(schemas: &[&Value]) -> Vec<String> {
+    let types = |schema: &Value| -> Vec<String> {
+        match &schema["type"] {
+            Value::String(t) => vec![t.clone()],
+            Value::Array(types) => types
+                .iter()
+                .filter_map(|t| t.as_str().map(String::from))
+                .collect(),
+            _ => vec![],
+        }
+    };
+    let listed = |schema: &Value| -> Vec<Value> {
+        schema["enum"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .chain(schema.get("const"))
+            .cloned()
+            .collect()
+    };
+
+    // Booleans are only worth listing when they're all that's allowed, rather than one choice
+    // among any string or number (such as an expression).  Listed values are kept either way,
+    // as suggestions.
+    let free = schemas.iter().any(|schema| {
+        listed(schema).is_empty()
+            && types(schema)
+                .iter()
+                .any(|t| ["string", "number", "integer"].contains(&t.as_str()))
+    });
+
+    let mut values: Vec<String> = vec![];
+
+    for schema in schemas {
+        let mut allowed = listed(schema);
+        if !free && types(schema) == ["boolean"] {
+            allowed.extend([Value::Bool(true), Value::Bool(false)]);
+        }
+
+        for value in allowed {
+            let value = match value {
+                Value::String(value) => value,
+                Value::Bool(_) | Value::Number(_) => value.to_string(),
+                _ => continue,
+            };
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        }
+    }
+
+    values
+}
@@ -0,0 +1,179 @@
+use serde_json::Value;
+
+use crate::{
+    config::ActionType,
+    source_map::SourceMap,
+    system,
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// Run every `run` script in a workflow or composite action that is written for a shell that
+/// shellcheck understands through `program`, and report what it finds.
+pub fn validate_scripts(
+    doc: &Value,
+    action_type: ActionType,
+    program: &str,
+    source_map: &SourceMap,
+    state: &mut ValidationState,
+) {
+    match action_type {
+        // Composite action steps have no default shell
+        ActionType::Action if doc["runs"]["using"] == "composite" => {
+            let scripts = ScriptSteps::new(&doc["runs"]["steps"], "/runs/steps", None);
+            check_scripts(scripts, program, source_map, state);
+        }
+        ActionType::Workflow => {
+            for (job_name, job) in doc["jobs"].as_object().into_iter().flatten() {
+                let default_shell = job["defaults"]["run"]["shell"]
+                    .as_str()
+                    .or_else(|| doc["defaults"]["run"]["shell"].as_str())
+                    .or_else(|| (!runs_on_windows(&job["runs-on"])).then_some("bash"));
+
+                let scripts = ScriptSteps::new(
+                    &job["steps"],
+                    &format!("/jobs/{job_name}/steps"),
+                    default_shell,
+                );
+                check_scripts(scripts, program, source_map, state);
+            }
+        }
+        // Nothing else runs scripts
+        _ => {}
+    }
+}
+
+/// The steps of a job (or composite action) which run a script, along with the shell each
+/// runs with.
+struct ScriptSteps<'a> {
+    steps: &'a Value,
+    path: String,
+    default_shell: Option<&'a str>,
+}
+
+impl<'a> ScriptSteps<'a> {
+    fn new(steps: &'a Value, path: &str, default_shell: Option<&'a str>) -> Self {
+        ScriptSteps {
+            steps,
+            path: path.into(),
+            default_shell,
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (String, &'a str, &'a str)> + '_ {
+        self.steps
+            .as_array()
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .filter_map(|(i, step)| {
+                let script = step["run"].as_str()?;
+                let shell = step["shell"].as_str().or(self.default_shell)?;
+                Some((format!("{}/{i}/run", self.path), shell, script))
+            })
+    }
+}
+
+fn check_scripts(
+    scripts: ScriptSteps,
+    program: &str,
+    source_map: &SourceMap,
+    state: &mut ValidationState,
+) {
+    for (path, shell, script) in scripts.iter() {
+        let Some(dialect) = dialect(shell) else {
+            continue;
+        };
+
+        let comments =
+            match system::shellcheck::check(program, dialect, &without_expressions(script)) {
+                Ok(comments) => comments,
+                Err(e) => {
+                    state.errors.push(ValidationError::ShellCheck {
+                        code: "shellcheck_failed".into(),
+                        severity: Severity::Error,
+                        path,
+                        title: "Failed to run shellcheck".into(),
+                        detail: Some(e.to_string()),
+                        location: None,
+                    });
+                    // It's not going to work any better for the next script
+                    return;
+                }
+            };
+
+        for comment in comments {
+            state.errors.push(ValidationError::ShellCheck {
+                code: "shellcheck".into(),
+                severity: severity(&comment.level),
+                path: path.clone(),
+                title: format!("ShellCheck SC{} ({})", comment.code, comment.level),
+                detail: Some(format!(
+                    "Line {} of the script: {}",
+                    comment.line, comment.message
+                )),
+                location: source_map.locate(&path, comment.line, comment.column),
+            });
+        }
+    }
+}
+
+/// Only shellcheck's errors fail validation; its style suggestions and notes are just shown.
+fn severity(level: &str) -> Severity {
+    match level {
+        "error" => Severity::Error,
+        "warning" => Severity::Warning,
+        _ => Severity::Info,
+    }
+}
+
+/// Work out which shell language shellcheck should check a script as, from a step's `shell`.
+/// Custom shells (like `bash -e {0}`) are recognised by the program they run.
+fn dialect(shell: &str) -> Option<&'static str> {
+    let program = shell.split_whitespace().next()?;
+    let program = program.rsplit('/').next().unwrap_or(program);
+
+    match program {
+        "bash" => Some("bash"),
+        "sh" => Some("sh"),
+        "dash" => Some("dash"),
+        "ksh" => Some("ksh"),
+        _ => None,
+    }
+}
+
+/// Jobs on Windows runners run their scripts with PowerShell unless told otherwise.
+fn runs_on_windows(runs_on: &Value) -> bool {
+    match runs_on {
+        Value::String(label) => label.to_lowercase().contains("windows"),
+        Value::Array(labels) => labels.iter().any(runs_on_windows),
+        Value::Object(runs_on) => runs_on_windows(&runs_on["labels"]),
+        _ => false,
+    }
+}
+
+/// Expressions are evaluated before the script is run, so shellcheck has no idea what to make
+/// of them.  Each is replaced with a placeholder of the same length, so that the positions of
+/// everything else in the script are unchanged.
+fn without_expressions(script: &str) -> String {
+    let mut result = String::with_capacity(script.len());
+    let mut rest = script;
+
+    while let Some(start) = rest.find("${{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let expression = &rest[start..start + end + 2];
+
+        result.push_str(&rest[..start]);
+        result.extend(
+            expression
+                .chars()
+                .map(|c| if c == '\n' { '\n' } else { '_' }),
+        );
+        rest = &rest[start + end + 2..];
+    }
+
+    result.push_str(rest);
+    result
+}
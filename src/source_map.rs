@@ -0,0 +1,244 @@
+use std::collections::{BTreeMap, HashMap};
+use yaml_rust2::{
+    parser::{MarkedEventReceiver, Parser},
+    scanner::{Marker, TScalarStyle},
+    Event,
+};
+
+use crate::validation_error::ParseErrorLocation;
+
+/// Where a value appears in the YAML source.
+#[derive(Clone, Copy, Debug)]
+pub struct Position {
+    /// The line the value starts on, counting from 1.
+    pub line: usize,
+    /// The column the value starts at, counting from 0.
+    pub column: usize,
+    /// Whether the value is a literal (`|`) or folded (`>`) block scalar, in which case
+    /// `line` and `column` are where its content starts, and each line of the content is on
+    /// its own line of the source.
+    pub block: bool,
+}
+
+/// The location of every value in a YAML document, keyed by the JSON pointer (in the same
+/// unescaped form used for `path` in validation errors) that refers to it.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    values: HashMap<String, Position>,
+    /// Where the key of each value in a mapping is.
+    keys: HashMap<String, Position>,
+    /// The node that each key and scalar starts, by the line and column it's at.
+    starts: BTreeMap<(usize, usize), String>,
+    /// The outermost node that starts on each line, where a node starts at its key if it's in a
+    /// mapping, or its `-` if it's in a sequence.
+    nodes_by_line: BTreeMap<usize, String>,
+    /// The lines spanned by each block scalar, and the indentation of its content.
+    blocks: Vec<Block>,
+    /// The (character) index at which each line of the source starts.
+    line_starts: Vec<usize>,
+}
+
+impl SourceMap {
+    /// Map out the first document in `src`.  Anything that can't be parsed is left unmapped,
+    /// because it will have been reported as a parse error already.
+    pub fn new(src: &str) -> Self {
+        let mut builder = Builder::default();
+        let _ = Parser::new_from_str(src).load(&mut builder, false);
+
+        let line_starts = std::iter::once(0)
+            .chain(
+                src.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c == '\n')
+                    .map(|(i, _)| i + 1),
+            )
+            .collect();
+
+        SourceMap {
+            values: builder.values,
+            keys: builder.keys,
+            starts: builder.starts,
+            nodes_by_line: builder.nodes_by_line,
+            blocks: builder.blocks,
+            line_starts,
+        }
+    }
+
+    pub fn position(&self, path: &str) -> Option<Position> {
+        self.values.get(path).copied()
+    }
+
+    /// Where the key of the value at `path` is, if the value is in a mapping.
+    #[cfg_attr(feature = "js", allow(dead_code))] // Only used by the language server
+    pub fn key_position(&self, path: &str) -> Option<Position> {
+        self.keys.get(path).copied()
+    }
+
+    /// The pointer to the node whose key or scalar value is at a (1-based) line and column.
+    pub fn path_at(&self, line: usize, column: usize) -> Option<&str> {
+        self.starts
+            .range((line, 0)..=(line, column.saturating_sub(1)))
+            .next_back()
+            .map(|(_, path)| path.as_str())
+    }
+
+    /// The pointer to the outermost node that starts on the given line, if any.
+    pub fn node_at_line(&self, line: usize) -> Option<&str> {
+        self.nodes_by_line.get(&line).map(String::as_str)
+    }
+
+    /// Whether a line is part of the content of a block scalar, rather than YAML in its own
+    /// right.  Lines that look like comments within a `run` script are part of the script.
+    pub fn in_block_scalar(&self, line: usize, indent: usize) -> bool {
+        self.blocks
+            .iter()
+            .any(|block| (block.start..block.end).contains(&line) && indent >= block.indent)
+    }
+
+    /// The location of a (1-based) line and column in the source.
+    pub fn location(&self, line: usize, column: usize) -> ParseErrorLocation {
+        let line_start = self.line_starts.get(line - 1).copied().unwrap_or_default();
+
+        ParseErrorLocation {
+            index: line_start + column.saturating_sub(1),
+            line,
+            column,
+        }
+    }
+
+    /// Find where a (1-based) line and column within a string value appear in the source.
+    /// Only block scalars can be mapped precisely; for any other value, where the value starts
+    /// is the best that can be done.
+    pub fn locate(&self, path: &str, line: usize, column: usize) -> Option<ParseErrorLocation> {
+        let position = self.position(path)?;
+
+        if position.block {
+            Some(self.location(
+                position.line + line.saturating_sub(1),
+                position.column + column.max(1),
+            ))
+        } else {
+            Some(self.location(position.line, position.column + 1))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Block {
+    start: usize,
+    /// The line after the last one that could be part of the block.
+    end: usize,
+    indent: usize,
+}
+
+enum Container {
+    Mapping { key: Option<String> },
+    Sequence { index: usize },
+}
+
+#[derive(Default)]
+struct Builder {
+    values: HashMap<String, Position>,
+    keys: HashMap<String, Position>,
+    starts: BTreeMap<(usize, usize), String>,
+    nodes_by_line: BTreeMap<usize, String>,
+    blocks: Vec<Block>,
+    /// A block scalar whose end isn't known until the next event.
+    open_block: Option<(usize, usize)>,
+    stack: Vec<(String, Container)>,
+}
+
+impl Builder {
+    /// Work out the pointer for a node that is starting, if it is a value rather than a key.
+    fn node_path(&mut self, scalar: Option<&str>, mark: Marker) -> Option<String> {
+        let line = mark.line();
+        let Some((path, container)) = self.stack.last_mut() else {
+            return Some(String::new());
+        };
+
+        let (path, starts) = match container {
+            Container::Mapping { key } => match key.take() {
+                Some(key) => return Some(format!("{path}/{key}")),
+                None => {
+                    // Keys which aren't scalars can't be referred to by a pointer, so a
+                    // placeholder is used to keep track of where the value goes
+                    let key = key.insert(scalar.unwrap_or("?").to_string());
+                    let path = format!("{path}/{key}");
+                    self.keys.insert(
+                        path.clone(),
+                        Position {
+                            line,
+                            column: mark.col(),
+                            block: false,
+                        },
+                    );
+                    self.starts.insert((line, mark.col()), path.clone());
+                    (path, None)
+                }
+            },
+            Container::Sequence { index } => {
+                *index += 1;
+                let path = format!("{path}/{}", *index - 1);
+                (path.clone(), Some(path))
+            }
+        };
+
+        // Outer nodes are added before the nodes within them, so they take precedence
+        self.nodes_by_line.entry(line).or_insert(path);
+
+        starts
+    }
+}
+
+impl MarkedEventReceiver for Builder {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        let position = |block| Position {
+            line: mark.line(),
+            column: mark.col(),
+            block,
+        };
+
+        if let Some((start, indent)) = self.open_block.take() {
+            self.blocks.push(Block {
+                start,
+                end: mark.line(),
+                indent,
+            });
+        }
+
+        match event {
+            Event::Scalar(value, style, ..) => {
+                let block = matches!(style, TScalarStyle::Literal | TScalarStyle::Folded);
+                if block {
+                    self.open_block = Some((mark.line(), mark.col()));
+                }
+                if let Some(path) = self.node_path(Some(&value), mark) {
+                    self.starts.insert((mark.line(), mark.col()), path.clone());
+                    self.values.insert(path, position(block));
+                }
+            }
+            Event::Alias(..) => {
+                if let Some(path) = self.node_path(None, mark) {
+                    self.values.insert(path, position(false));
+                }
+            }
+            Event::MappingStart(..) | Event::SequenceStart(..) => {
+                // A collection used as a key gets mapped under a placeholder that nothing
+                // will ever look up, which is harmless
+                let path = self.node_path(None, mark).unwrap_or_else(|| "?".into());
+                self.values.insert(path.clone(), position(false));
+
+                let container = if matches!(event, Event::MappingStart(..)) {
+                    Container::Mapping { key: None }
+                } else {
+                    Container::Sequence { index: 0 }
+                };
+                self.stack.push((path, container));
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{
+    source_map::SourceMap,
+    validation_error::{Severity, ValidationError},
+};
+
+const DIRECTIVE_PREFIX: &str = "action-validator:";
+
+/// A `# action-validator: ignore...` comment.
+struct Suppression {
+    /// The line the comment is on, counting from 1.
+    line: usize,
+    /// The column the comment starts at, counting from 1.
+    column: usize,
+    /// What the suppression applies to.
+    scope: Scope,
+    /// The codes being suppressed, and whether anything was suppressed for each.  No codes
+    /// means that everything is suppressed.
+    codes: Vec<(String, bool)>,
+    /// Whether anything was suppressed, for a suppression without codes.
+    used: bool,
+}
+
+enum Scope {
+    File,
+    Node(String),
+    /// The comment wasn't attached to anything that could be found.
+    Nothing,
+}
+
+impl Suppression {
+    fn covers(&self, path: &str) -> bool {
+        match &self.scope {
+            Scope::File => true,
+            Scope::Node(node) => {
+                path == node
+                    || path
+                        .strip_prefix(node.as_str())
+                        .is_some_and(|rest| rest.starts_with('/'))
+            }
+            Scope::Nothing => false,
+        }
+    }
+
+    /// Mark the suppression as used if it suppresses `error`.
+    fn suppresses(&mut self, error: &ValidationError) -> bool {
+        if !self.covers(error.path()) {
+            return false;
+        }
+
+        if self.codes.is_empty() {
+            self.used = true;
+            return true;
+        }
+
+        match self.codes.iter_mut().find(|(code, _)| code == error.code()) {
+            Some((_, used)) => {
+                *used = true;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Drop the findings that are suppressed by comments in `src`, and return findings for
+/// suppressions that are malformed, or which didn't suppress anything.
+pub fn apply(
+    src: &str,
+    source_map: &SourceMap,
+    errors: &mut Vec<ValidationError>,
+) -> Vec<ValidationError> {
+    let mut problems = vec![];
+    let mut suppressions = parse(src, source_map, &mut problems);
+
+    if suppressions.is_empty() {
+        return problems;
+    }
+
+    errors.retain(|error| {
+        // Stop at the first suppression that applies, so later ones aren't marked as used
+        !suppressions
+            .iter_mut()
+            .any(|suppression| suppression.suppresses(error))
+    });
+
+    for suppression in suppressions {
+        let location = Some(source_map.location(suppression.line, suppression.column));
+        let path = match &suppression.scope {
+            Scope::Node(node) => node.clone(),
+            _ => String::new(),
+        };
+        let what = match suppression.scope {
+            Scope::File => "file-level suppression",
+            _ => "suppression",
+        };
+
+        let unused: Vec<&str> = if suppression.codes.is_empty() {
+            if suppression.used {
+                continue;
+            }
+            vec![]
+        } else {
+            let unused: Vec<&str> = suppression
+                .codes
+                .iter()
+                .filter(|(_, used)| !used)
+                .map(|(code, _)| code.as_str())
+                .collect();
+            if unused.is_empty() {
+                continue;
+            }
+            unused
+        };
+
+        let detail = if let Scope::Nothing = suppression.scope {
+            format!(
+                "The suppression on line {} isn't attached to anything, so it can't suppress anything",
+                suppression.line
+            )
+        } else if unused.is_empty() {
+            format!(
+                "The {what} on line {} didn't suppress anything, so it can be removed",
+                suppression.line
+            )
+        } else {
+            format!(
+                "The {what} on line {} didn't suppress any {} findings, so {} can be removed",
+                suppression.line,
+                unused.join(", "),
+                if unused.len() == 1 { "it" } else { "they" },
+            )
+        };
+
+        problems.push(ValidationError::Suppression {
+            code: "unused_suppression".into(),
+            severity: Severity::Warning,
+            path,
+            title: "Unused suppression".into(),
+            detail: Some(detail),
+            location,
+        });
+    }
+
+    problems
+}
+
+fn parse(
+    src: &str,
+    source_map: &SourceMap,
+    problems: &mut Vec<ValidationError>,
+) -> Vec<Suppression> {
+    let lines: Vec<&str> = src.lines().collect();
+    let mut suppressions = vec![];
+
+    for (i, text) in lines.iter().enumerate() {
+        let line = i + 1;
+        let indent = text.len() - text.trim_start().len();
+        let Some((start, directive)) = find_directive(text) else {
+            continue;
+        };
+        // Anything that looks like a comment within a `run` script belongs to the script
+        if source_map.in_block_scalar(line, indent) {
+            continue;
+        }
+        let column = text[..start].chars().count() + 1;
+
+        let Some((file, codes)) = parse_directive(directive) else {
+            problems.push(ValidationError::Suppression {
+                code: "invalid_suppression".into(),
+                severity: Severity::Error,
+                path: String::new(),
+                title: "Invalid suppression".into(),
+                detail: Some(format!(
+                    "The comment on line {line} isn't a valid suppression: expected ignore, ignore[codes], ignore-file or ignore-file[codes] after {DIRECTIVE_PREFIX}"
+                )),
+                location: Some(source_map.location(line, column)),
+            });
+            continue;
+        };
+
+        let scope = if file {
+            Scope::File
+        } else if !text[..start].trim().is_empty() {
+            // A comment at the end of a line applies to what's on that line
+            node_scope(source_map, line)
+        } else {
+            // A comment on a line of its own applies to whatever comes next
+            let next = lines
+                .iter()
+                .enumerate()
+                .skip(line)
+                .find(|(_, text)| {
+                    let text = text.trim();
+                    !text.is_empty() && !text.starts_with('#')
+                })
+                .map(|(i, _)| i + 1);
+            match next {
+                Some(next) => node_scope(source_map, next),
+                None => Scope::Nothing,
+            }
+        };
+
+        suppressions.push(Suppression {
+            line,
+            column,
+            scope,
+            codes: codes.into_iter().map(|code| (code, false)).collect(),
+            used: false,
+        });
+    }
+
+    suppressions
+}
+
+fn node_scope(source_map: &SourceMap, line: usize) -> Scope {
+    match source_map.node_at_line(line) {
+        Some(node) => Scope::Node(node.to_string()),
+        None => Scope::Nothing,
+    }
+}
+
+/// Find an `action-validator:` comment on a line, returning where the comment starts and
+/// the text of the directive that follows the prefix.
+fn find_directive(text: &str) -> Option<(usize, &str)> {
+    text.match_indices('#').find_map(|(start, _)| {
+        // A `#` only starts a comment at the start of a line, or after whitespace
+        let starts_comment = text[..start]
+            .chars()
+            .next_back()
+            .is_none_or(char::is_whitespace);
+        let directive = text[start + 1..]
+            .trim_start()
+            .strip_prefix(DIRECTIVE_PREFIX)?;
+
+        starts_comment.then_some((start, directive.trim()))
+    })
+}
+
+/// Parse the directive in an `action-validator:` comment, returning whether it applies to the
+/// whole file, and the codes it suppresses.  Anything after the directive and whitespace is a
+/// free-form explanation.
+fn parse_directive(directive: &str) -> Option<(bool, Vec<String>)> {
+    let name_end = directive
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(directive.len());
+    let file = match &directive[..name_end] {
+        "ignore" => false,
+        "ignore-file" => true,
+        _ => return None,
+    };
+
+    let rest = &directive[name_end..];
+    let (codes, rest) = match rest.strip_prefix('[') {
+        Some(rest) => {
+            let (codes, rest) = rest.split_once(']')?;
+            let codes: Vec<String> = codes
+                .split(',')
+                .map(|code| code.trim().to_string())
+                .collect();
+            if codes.iter().any(String::is_empty) {
+                return None;
+            }
+            (codes, rest)
+        }
+        None => (vec![], rest),
+    };
+
+    (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some((file, codes))
+}
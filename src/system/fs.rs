@@ -8,6 +8,12 @@ mod js_fs {
     extern "C" {
         #[wasm_bindgen(catch, js_namespace = fs, js_name = readFileSync)]
         pub fn read_file_sync(path: &str, encoding: &str) -> Result<String, js_sys::Error>;
+
+        #[wasm_bindgen(catch, js_namespace = fs, js_name = writeFileSync)]
+        pub fn write_file_sync(path: &str, data: &str) -> Result<(), js_sys::Error>;
+
+        #[wasm_bindgen(js_namespace = fs, js_name = existsSync)]
+        pub fn exists_sync(path: &str) -> bool;
     }
 }
 
@@ -27,3 +33,36 @@ where
 {
     std::fs::read_to_string(path).map_err(|e| e.to_string())
 }
+
+#[cfg(feature = "js")]
+pub fn write<P>(path: P, contents: &str) -> Result<(), String>
+where
+    P: AsRef<Path>,
+{
+    js_fs::write_file_sync(path.as_ref().to_string_lossy().as_ref(), contents)
+        .map_err(|e| format!("{}", e.to_string()))
+}
+
+#[cfg(not(feature = "js"))]
+pub fn write<P>(path: P, contents: &str) -> Result<(), String>
+where
+    P: AsRef<Path>,
+{
+    std::fs::write(path, contents).map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "js")]
+pub fn exists<P>(path: P) -> bool
+where
+    P: AsRef<Path>,
+{
+    js_fs::exists_sync(path.as_ref().to_string_lossy().as_ref())
+}
+
+#[cfg(not(feature = "js"))]
+pub fn exists<P>(path: P) -> bool
+where
+    P: AsRef<Path>,
+{
+    path.as_ref().exists()
+}
@@ -1,3 +1,4 @@
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 pub fn ls_files() -> Result<Vec<String>, std::io::Error> {
@@ -18,3 +19,53 @@ pub fn ls_files() -> Result<Vec<String>, std::io::Error> {
 
     Ok(files)
 }
+
+/// List the files in `dir` which git would consider part of the repository: those that are
+/// tracked, and those that are new but not ignored.
+pub fn ls_files_in(dir: &Path) -> Result<Vec<String>, std::io::Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "git ls-files failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let mut files: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect();
+    // Files with merge conflicts are listed once for each side of the conflict
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+/// The top-level directory of the repository that the current directory is in, if any.
+pub fn toplevel() -> Option<PathBuf> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim(),
+    ))
+}
@@ -0,0 +1,48 @@
+use serde::Deserialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// A problem that shellcheck found in a script.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    pub line: usize,
+    pub column: usize,
+    pub level: String,
+    pub code: u32,
+    pub message: String,
+}
+
+#[derive(Deserialize)]
+struct Output {
+    comments: Vec<Comment>,
+}
+
+/// Run `program` (which should be shellcheck) over a script written for `shell`.
+pub fn check(program: &str, shell: &str, script: &str) -> Result<Vec<Comment>, std::io::Error> {
+    let mut child = Command::new(program)
+        .args(["--format=json1", &format!("--shell={shell}"), "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    child
+        .stdin
+        .take()
+        .expect("stdin to be piped")
+        .write_all(script.as_bytes())?;
+    let output = child.wait_with_output()?;
+
+    // shellcheck exits with 1 when it finds problems, and anything else when it fails
+    if !matches!(output.status.code(), Some(0 | 1)) {
+        return Err(std::io::Error::other(format!(
+            "{program} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    serde_json::from_slice::<Output>(&output.stdout)
+        .map(|output| output.comments)
+        .map_err(|e| std::io::Error::other(format!("could not read {program} output: {e}")))
+}
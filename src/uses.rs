@@ -0,0 +1,339 @@
+use serde_json::Value;
+
+use crate::{
+    config::ActionType,
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// Something that a step (or a job calling a reusable workflow) `uses`.
+#[derive(Debug)]
+pub enum Reference<'a> {
+    /// An action (or workflow) in the same repository, such as `./.github/actions/build`.
+    Local { path: &'a str },
+    /// A Docker image, such as `docker://alpine:3.20`.
+    Docker {
+        tag: Option<&'a str>,
+        digest: Option<&'a str>,
+    },
+    /// An action (or workflow) in another repository, such as `actions/checkout@v4`.
+    Repository {
+        owner: &'a str,
+        repo: &'a str,
+        path: Option<&'a str>,
+        git_ref: &'a str,
+    },
+}
+
+impl<'a> Reference<'a> {
+    /// Parse a `uses` value, describing what is wrong with it if it isn't valid.
+    pub fn parse(uses: &'a str) -> Result<Self, String> {
+        if uses.contains("${{") {
+            return Err("expressions cannot be used in uses".into());
+        }
+
+        if let Some(path) = uses.strip_prefix("./") {
+            if path.contains('@') {
+                return Err(
+                    "local actions always come from the same commit as the workflow, so they cannot have a @ref".into(),
+                );
+            }
+            return Ok(Reference::Local { path });
+        }
+
+        if let Some(image) = uses.strip_prefix("docker://") {
+            return parse_docker(image);
+        }
+
+        if uses.starts_with('/') {
+            return Err("repository references cannot start with /; use owner/repo@ref, or ./path for an action in this repository".into());
+        }
+
+        let Some((name, git_ref)) = uses.split_once('@') else {
+            return Err(
+                "missing a @ref; use owner/repo@ref, where ref is a tag, branch, or commit SHA"
+                    .into(),
+            );
+        };
+
+        if git_ref.is_empty() {
+            return Err("the @ref is empty; give a tag, branch, or commit SHA after the @".into());
+        }
+        if git_ref.contains('@') || git_ref.chars().any(char::is_whitespace) {
+            return Err(format!("{git_ref} is not a valid git ref"));
+        }
+
+        if name.is_empty() {
+            return Err("missing an owner and repository before the @; use owner/repo@ref".into());
+        }
+
+        let mut parts = name.splitn(3, '/');
+        let owner = parts.next().unwrap_or_default();
+        let Some(repo) = parts.next() else {
+            return Err(format!(
+                "{name} is missing a repository name; use owner/repo@ref"
+            ));
+        };
+        let path = parts.next();
+
+        if owner.is_empty() || !owner.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("{owner:?} is not a valid repository owner"));
+        }
+        if repo.is_empty()
+            || !repo
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(format!("{repo:?} is not a valid repository name"));
+        }
+        if let Some(path) = path {
+            if path.is_empty() || path.split('/').any(str::is_empty) {
+                return Err(format!(
+                    "{path:?} is not a valid path within {owner}/{repo}"
+                ));
+            }
+        }
+
+        Ok(Reference::Repository {
+            owner,
+            repo,
+            path,
+            git_ref,
+        })
+    }
+
+    /// Whether this refers to a reusable workflow, rather than an action.
+    pub fn is_workflow(&self) -> bool {
+        let path = match self {
+            Reference::Local { path } => *path,
+            Reference::Repository {
+                path: Some(path), ..
+            } => path,
+            _ => return false,
+        };
+
+        path.starts_with(".github/workflows/")
+            && (path.ends_with(".yml") || path.ends_with(".yaml"))
+    }
+}
+
+fn parse_docker(image: &str) -> Result<Reference<'_>, String> {
+    let (image, digest) = match image.split_once('@') {
+        Some((image, digest)) => (image, Some(digest)),
+        None => (image, None),
+    };
+
+    // A colon before the last slash is a registry port, not a tag
+    let (image, tag) = match image.rsplit_once(':') {
+        Some((name, tag)) if !tag.contains('/') => (name, Some(tag)),
+        _ => (image, None),
+    };
+
+    if image.is_empty() {
+        return Err("missing an image name after docker://".into());
+    }
+
+    let mut components: Vec<&str> = image.split('/').collect();
+    let first = components[0];
+    if components.len() > 1 && (first.contains('.') || first.contains(':') || first == "localhost")
+    {
+        // The registry host can be anything a hostname can be, so there's not much to check
+        components.remove(0);
+        if first.starts_with(':') || first.ends_with(':') {
+            return Err(format!("{first} is not a valid registry"));
+        }
+    }
+
+    for component in components {
+        let valid = !component.is_empty()
+            && component.split(['.', '_', '-']).all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            });
+        if !valid {
+            return Err(format!(
+                "{image} is not a valid image name; names may only contain lowercase letters, digits, and separators (., _, -, /)"
+            ));
+        }
+    }
+
+    if let Some(tag) = tag {
+        let valid = !tag.is_empty()
+            && tag.len() <= 128
+            && !tag.starts_with(['.', '-'])
+            && tag
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+        if !valid {
+            return Err(format!(
+                "{tag:?} is not a valid image tag; tags may only contain letters, digits, _, . and -, and cannot start with . or -"
+            ));
+        }
+    }
+
+    if let Some(digest) = digest {
+        let valid = digest.split_once(':').is_some_and(|(algorithm, hex)| {
+            !algorithm.is_empty()
+                && algorithm
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+                && hex.len() >= 32
+                && hex
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+        });
+        if !valid {
+            return Err(format!(
+                "{digest:?} is not a valid image digest; digests look like sha256:<64 hex digits>"
+            ));
+        }
+    }
+
+    Ok(Reference::Docker { tag, digest })
+}
+
+/// Where a `uses` appears.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Site {
+    /// In a step, where it names an action.
+    Step,
+    /// In a job, where it names a reusable workflow.
+    Job,
+}
+
+/// Find the JSON pointer, location and value of every `uses` in a workflow or action.
+pub fn all_uses(doc: &Value, action_type: ActionType) -> Vec<(String, Site, &str)> {
+    fn steps<'a>(steps: &'a Value, path: &str, found: &mut Vec<(String, Site, &'a str)>) {
+        for (i, step) in steps.as_array().into_iter().flatten().enumerate() {
+            if let Some(uses) = step["uses"].as_str() {
+                found.push((format!("{path}/{i}/uses"), Site::Step, uses));
+            }
+        }
+    }
+
+    let mut found = vec![];
+
+    match action_type {
+        ActionType::Action => steps(&doc["runs"]["steps"], "/runs/steps", &mut found),
+        ActionType::Workflow => {
+            for (job_name, job) in doc["jobs"].as_object().into_iter().flatten() {
+                if let Some(uses) = job["uses"].as_str() {
+                    found.push((format!("/jobs/{job_name}/uses"), Site::Job, uses));
+                }
+                steps(
+                    &job["steps"],
+                    &format!("/jobs/{job_name}/steps"),
+                    &mut found,
+                );
+            }
+        }
+        // Nothing else has steps
+        _ => {}
+    }
+
+    found
+}
+
+pub fn validate_uses(doc: &Value, action_type: ActionType, state: &mut ValidationState) {
+    for (path, site, uses) in all_uses(doc, action_type) {
+        let reference = match Reference::parse(uses) {
+            Ok(reference) => reference,
+            Err(problem) => {
+                state.errors.push(ValidationError::InvalidUses {
+                    code: "invalid_uses".into(),
+                    severity: Severity::Error,
+                    path,
+                    title: "Invalid uses".into(),
+                    detail: Some(format!("{uses} is not valid: {problem}")),
+                });
+                continue;
+            }
+        };
+
+        match site {
+            Site::Job if !reference.is_workflow() => {
+                state.errors.push(ValidationError::InvalidUses {
+                    code: "job_uses_not_workflow".into(),
+                    severity: Severity::Error,
+                    path,
+                    title: "Job uses something other than a workflow".into(),
+                    detail: Some(format!(
+                        "Jobs can only use reusable workflows (./.github/workflows/<file>.yml or owner/repo/.github/workflows/<file>.yml@ref), but {uses} is not a workflow file; actions must be used in a step"
+                    )),
+                });
+            }
+            Site::Step if reference.is_workflow() => {
+                state.errors.push(ValidationError::InvalidUses {
+                    code: "step_uses_workflow".into(),
+                    severity: Severity::Error,
+                    path,
+                    title: "Step uses a workflow".into(),
+                    detail: Some(format!(
+                        "{uses} is a reusable workflow, which must be used by a job, not a step"
+                    )),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn validate_pinning(
+    doc: &Value,
+    action_type: ActionType,
+    trusted_owners: &[String],
+    state: &mut ValidationState,
+) {
+    for (path, _, uses) in all_uses(doc, action_type) {
+        let problem = match Reference::parse(uses) {
+            Ok(Reference::Repository {
+                owner,
+                repo,
+                git_ref,
+                ..
+            }) => {
+                if is_full_sha(git_ref) || is_trusted(owner, repo, trusted_owners) {
+                    continue;
+                }
+
+                if git_ref.len() >= 7 && git_ref.chars().all(|c| c.is_ascii_hexdigit()) {
+                    format!("{uses} is pinned to an abbreviated commit SHA, which could become ambiguous; use the full 40-character commit SHA instead")
+                } else {
+                    format!("{uses} is pinned to {git_ref}, which can be changed to point to different code; pin it to a full 40-character commit SHA instead")
+                }
+            }
+            Ok(Reference::Docker {
+                digest: None, tag, ..
+            }) => {
+                let tag = tag.unwrap_or("latest");
+                format!("{uses} uses the image tagged {tag}, which can be changed to point to a different image; pin it to an image digest (@sha256:...) instead")
+            }
+            _ => continue,
+        };
+
+        state.errors.push(ValidationError::UnpinnedAction {
+            code: "unpinned_action".into(),
+            severity: Severity::Error,
+            path,
+            title: "Action is not pinned".into(),
+            detail: Some(problem),
+        });
+    }
+}
+
+fn is_full_sha(git_ref: &str) -> bool {
+    git_ref.len() == 40 && git_ref.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Trusted owners can be given as `owner` or `owner/*` to trust every repository belonging to
+/// that owner, or `owner/repo` to trust a single repository.
+fn is_trusted(owner: &str, repo: &str, trusted_owners: &[String]) -> bool {
+    trusted_owners.iter().any(|trusted| {
+        let (trusted_owner, trusted_repo) = trusted.split_once('/').unwrap_or((trusted, "*"));
+
+        trusted_owner.eq_ignore_ascii_case(owner)
+            && (trusted_repo == "*" || trusted_repo.eq_ignore_ascii_case(repo))
+    })
+}
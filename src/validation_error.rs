@@ -22,6 +22,16 @@ impl From<yaml_serde::Location> for ParseErrorLocation {
     }
 }
 
+/// How serious a finding is.  Only errors cause validation to fail, unless warnings are
+/// denied too.
+#[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
 macro_rules! validation_errors {
     ($( $name:ident $( { $($fields:tt)* } )? ),*) => {
         #[derive(Serialize, Debug)]
@@ -31,6 +41,7 @@ macro_rules! validation_errors {
             $(
                 $name {
                     code: String,
+                    severity: Severity,
                     detail: Option<String>,
                     path: String,
                     title: String,
@@ -38,6 +49,44 @@ macro_rules! validation_errors {
                 },
             )*
         }
+
+        impl ValidationError {
+            pub fn code(&self) -> &str {
+                match self {
+                    $( ValidationError::$name { code, .. } => code, )*
+                }
+            }
+
+            pub fn path(&self) -> &str {
+                match self {
+                    $( ValidationError::$name { path, .. } => path, )*
+                }
+            }
+
+            pub fn title(&self) -> &str {
+                match self {
+                    $( ValidationError::$name { title, .. } => title, )*
+                }
+            }
+
+            pub fn detail(&self) -> Option<&str> {
+                match self {
+                    $( ValidationError::$name { detail, .. } => detail.as_deref(), )*
+                }
+            }
+
+            pub fn severity(&self) -> Severity {
+                match self {
+                    $( ValidationError::$name { severity, .. } => *severity, )*
+                }
+            }
+
+            pub fn set_severity(&mut self, new_severity: Severity) {
+                match self {
+                    $( ValidationError::$name { severity, .. } => *severity = new_severity, )*
+                }
+            }
+        }
     };
 }
 
@@ -73,6 +122,24 @@ validation_errors!(
     UnresolvedJob,
     InvalidGlob,
     NoFilesMatchingGlob,
+    IneffectiveGlob,
+    InvalidMatrix,
+    UnresolvedMatrixKey,
+    InvalidRunner,
+    Permissions,
+    ScriptInjection,
+    PrivilegedCheckout { steps: Vec<String> },
+    UnpinnedAction,
+    InvalidUses,
+    ActionRuns,
+    CompositeIo,
+    DeprecatedCommand,
+    ShellCheck { location: Option<ParseErrorLocation> },
+    Dependabot,
+    IssueForm,
+    Suppression { location: Option<ParseErrorLocation> },
+    // Errors reported by rules from outside the crate
+    Custom,
     // Other Errors
     Parse { location: Option<ParseErrorLocation> }
 );
@@ -88,6 +155,7 @@ macro_rules! impl_from_valico_error {
                     else if let Some($err) = err.downcast_ref::<valico::json_schema::errors::$name>() {
                         ValidationError::$name {
                             code: $err.get_code().into(),
+                            severity: Severity::Error,
                             path: $err.get_path().into(),
                             title: $err.get_title().into(),
                             detail: $err.get_detail().map(|s| s.into()),
@@ -98,6 +166,7 @@ macro_rules! impl_from_valico_error {
                 else {
                     ValidationError::Unknown {
                         code: err.get_code().into(),
+                        severity: Severity::Error,
                         path: err.get_path().into(),
                         title: err.get_title().into(),
                         detail: err.get_detail().map(|s| s.into()),
@@ -140,6 +209,7 @@ impl From<yaml_serde::Error> for ValidationError {
     fn from(err: yaml_serde::Error) -> Self {
         ValidationError::Parse {
             code: "parse_error".into(),
+            severity: Severity::Error,
             detail: Some(err.to_string()),
             location: err.location().map(ParseErrorLocation::from),
             path: "".into(),
@@ -1,6 +1,9 @@
 use serde::Serialize;
 
-use crate::{config::ActionType, validation_error::ValidationError};
+use crate::{
+    config::ActionType,
+    validation_error::{Severity, ValidationError},
+};
 
 #[derive(Serialize, Debug)]
 pub struct ValidationState {
@@ -12,8 +15,16 @@ pub struct ValidationState {
 }
 
 impl ValidationState {
+    /// Whether there are no findings serious enough to fail validation.
     pub fn is_valid(&self) -> bool {
-        self.errors.is_empty()
+        self.count(Severity::Error) == 0
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.errors
+            .iter()
+            .filter(|error| error.severity() == severity)
+            .count()
     }
 }
 
@@ -29,10 +40,15 @@ impl From<valico::json_schema::ValidationState> for ValidationState {
 
 impl From<&valico::json_schema::ValidationState> for ValidationState {
     fn from(state: &valico::json_schema::ValidationState) -> Self {
+        let mut errors: Vec<ValidationError> = state.errors.iter().map(|err| err.into()).collect();
+        // valico checks a schema's keywords in hash order, so errors from the same
+        // subschema need sorting to come out the same way every time
+        errors.sort_by(|a, b| a.code().cmp(b.code()));
+
         ValidationState {
             file_path: None,
             action_type: None,
-            errors: state.errors.iter().map(|err| err.into()).collect(),
+            errors,
         }
     }
 }
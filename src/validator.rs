@@ -0,0 +1,291 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::{
+    config::{ActionType, RunConfig},
+    explain::{self, Explanation},
+    rules::Rule,
+    schemas,
+    source_map::SourceMap,
+    system,
+    validation_state::ValidationState,
+    CliConfig,
+};
+
+/// Validates GitHub Actions and Workflows, with the same checks as the `action-validator`
+/// command, but returning what it finds rather than printing it.
+///
+/// Options are set by chaining calls onto [`Validator::new`], and the same validator can then
+/// be used for any number of files:
+///
+/// ```
+/// use action_validator::{ActionType, Validator};
+///
+/// let validator = Validator::new()
+///     .action_type(ActionType::Workflow)
+///     .runner_labels(["gpu"]);
+///
+/// let state = validator.validate_str(
+///     "on: push
+/// jobs:
+///   test:
+///     needs: build
+///     runs-on: [self-hosted, gpu]
+///     steps:
+///       - run: make test
+/// ",
+/// );
+/// assert!(!state.is_valid());
+/// assert_eq!(state.errors[0].code(), "unresolved_job");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Validator {
+    action_type: Option<ActionType>,
+    verbose: bool,
+    rootdir: Option<PathBuf>,
+    runner_labels: Vec<String>,
+    runner_groups: Vec<String>,
+    trusted_owners: Vec<String>,
+    shellcheck: Option<String>,
+    workflow_schema: Option<Value>,
+    action_schema: Option<Value>,
+    allow: Vec<String>,
+    info: Vec<String>,
+    warn: Vec<String>,
+    deny: Vec<String>,
+    rules: Vec<Arc<dyn Rule>>,
+}
+
+impl Validator {
+    /// A validator with the same defaults as the command line.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validate everything as this type of file.  Otherwise, the type of each file is worked
+    /// out from its name and the directory it's in (see [`ActionType::detect`]), and anything
+    /// without a name is validated as a workflow.
+    pub fn action_type(mut self, action_type: ActionType) -> Self {
+        self.action_type = Some(action_type);
+        self
+    }
+
+    /// Log what's being done to the console.
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Match the `paths` globs in workflows against the files in this directory, rather than
+    /// the current directory.
+    pub fn rootdir(mut self, rootdir: impl Into<PathBuf>) -> Self {
+        self.rootdir = Some(rootdir.into());
+        self
+    }
+
+    /// Accept these as the labels of self-hosted runners in `runs-on`.
+    pub fn runner_labels(mut self, labels: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.runner_labels = labels.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Accept these as the names of runner groups in `runs-on`.
+    pub fn runner_groups(mut self, groups: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.runner_groups = groups.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Allow actions belonging to these owners (`owner/*`) or repositories (`owner/repo`) to
+    /// be pinned to a tag or branch, rather than a commit SHA.
+    pub fn trusted_owners(mut self, owners: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.trusted_owners = owners.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Check `run` scripts by running this shellcheck program.
+    pub fn shellcheck(mut self, program: impl Into<String>) -> Self {
+        self.shellcheck = Some(program.into());
+        self
+    }
+
+    /// Validate workflows against this JSON Schema, rather than the one built in.  Fails if
+    /// `schema` isn't a schema that can be validated against.
+    ///
+    /// ```
+    /// use action_validator::Validator;
+    /// use serde_json::json;
+    ///
+    /// assert!(Validator::new().workflow_schema(json!({ "type": 5 })).is_err());
+    /// ```
+    pub fn workflow_schema(mut self, schema: Value) -> Result<Self, String> {
+        schemas::check(&schema).map_err(|e| format!("Invalid workflow schema: {e}"))?;
+        self.workflow_schema = Some(schema);
+        Ok(self)
+    }
+
+    /// Validate actions against this JSON Schema, rather than the one built in.  Fails if
+    /// `schema` isn't a schema that can be validated against.
+    pub fn action_schema(mut self, schema: Value) -> Result<Self, String> {
+        schemas::check(&schema).map_err(|e| format!("Invalid action schema: {e}"))?;
+        self.action_schema = Some(schema);
+        Ok(self)
+    }
+
+    /// Don't report findings with these codes.
+    pub fn allow(mut self, codes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.allow = codes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Report findings with these codes as information.
+    pub fn info(mut self, codes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.info = codes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Report findings with these codes as warnings.
+    pub fn warn(mut self, codes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.warn = codes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Report findings with these codes as errors, even if they're not reported by default.
+    pub fn deny(mut self, codes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.deny = codes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Run a rule of your own on every file, after the built-in ones.
+    pub fn rule(mut self, rule: impl Rule + 'static) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Validate YAML source.  Files an action refers to can't be checked, because there's no
+    /// directory to look for them in.
+    pub fn validate_str(&self, src: &str) -> ValidationState {
+        crate::run(&self.run_config(src, None))
+    }
+
+    /// Read and validate a file.  The only error is failing to read the file; everything else
+    /// is reported in the returned state.
+    pub fn validate_file(&self, path: impl AsRef<Path>) -> Result<ValidationState, String> {
+        let path = path.as_ref();
+        let src = system::fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read file {}: {e}", path.display()))?;
+        let path = path.to_string_lossy();
+
+        Ok(crate::run(&self.run_config(&src, Some(&path))))
+    }
+
+    /// Validate YAML source as the file at `path`, whether or not that's what is in the file
+    /// right now, such as when the file is being edited.
+    pub fn validate_source(&self, path: impl AsRef<Path>, src: &str) -> ValidationState {
+        let path = path.as_ref().to_string_lossy();
+
+        crate::run(&self.run_config(src, Some(&path)))
+    }
+
+    /// Validate a document which has already been parsed.  Without the source, suppression
+    /// comments aren't seen, and findings from shellcheck can't be given a location.
+    pub fn validate_value(&self, doc: &Value) -> ValidationState {
+        crate::run_doc(&self.run_config("", None), doc)
+    }
+
+    /// The type of file that the file at `file_path` is validated as.
+    pub(crate) fn action_type_of(&self, file_path: Option<&str>) -> ActionType {
+        let detected = file_path.map_or(ActionType::Workflow, |path| {
+            ActionType::detect(Path::new(path))
+        });
+
+        self.action_type.unwrap_or(detected)
+    }
+
+    /// The schema that a type of file is validated against, if it has one.
+    pub(crate) fn schema(&self, action_type: ActionType) -> Option<Value> {
+        let configured = match action_type {
+            ActionType::Action => self.action_schema.clone(),
+            ActionType::Workflow => self.workflow_schema.clone(),
+            _ => None,
+        };
+
+        configured.or_else(|| schemas::embedded(action_type).map(schemas::parse_embedded))
+    }
+
+    /// Explain the value at `path` in a type of file (`path` being a pointer, like the `path`
+    /// of a finding), from the schema that type of file is validated against.
+    ///
+    /// ```
+    /// use action_validator::{ActionType, Validator};
+    ///
+    /// let explanation = Validator::new()
+    ///     .explain_path(ActionType::Workflow, "/jobs/build/steps/0/shell")
+    ///     .unwrap();
+    /// assert!(explanation.allowed_values.contains(&"bash".to_string()));
+    /// assert!(explanation.docs_url.is_some());
+    /// ```
+    pub fn explain_path(&self, action_type: ActionType, path: &str) -> Option<Explanation> {
+        explain::explain(&self.schema(action_type)?, path)
+    }
+
+    /// Explain the key or value at a (1-based) line and column of YAML source.
+    pub fn explain_position(
+        &self,
+        action_type: ActionType,
+        src: &str,
+        line: usize,
+        column: usize,
+    ) -> Option<Explanation> {
+        let source_map = SourceMap::new(src);
+
+        self.explain_path(action_type, source_map.path_at(line, column)?)
+    }
+
+    fn run_config<'a>(&'a self, src: &'a str, file_path: Option<&'a str>) -> RunConfig<'a> {
+        let file_name = file_path
+            .and_then(|path| Path::new(path).file_name())
+            .and_then(|name| name.to_str());
+
+        RunConfig {
+            file_path,
+            file_name,
+            action_type: self.action_type_of(file_path),
+            src,
+            verbose: self.verbose,
+            rootdir: self.rootdir.clone(),
+            runner_labels: &self.runner_labels,
+            runner_groups: &self.runner_groups,
+            trusted_owners: &self.trusted_owners,
+            shellcheck: self.shellcheck.as_deref(),
+            workflow_schema: self.workflow_schema.as_ref(),
+            action_schema: self.action_schema.as_ref(),
+            allow: &self.allow,
+            info: &self.info,
+            warn: &self.warn,
+            deny: &self.deny,
+            rules: &self.rules,
+        }
+    }
+}
+
+impl From<&CliConfig> for Validator {
+    fn from(config: &CliConfig) -> Self {
+        Validator {
+            action_type: None,
+            verbose: config.verbose,
+            rootdir: config.rootdir.clone(),
+            runner_labels: config.runner_labels.clone(),
+            runner_groups: config.runner_groups.clone(),
+            trusted_owners: config.trusted_owners.clone(),
+            shellcheck: config.shellcheck.clone(),
+            workflow_schema: None,
+            action_schema: None,
+            allow: config.allow.clone(),
+            info: config.info.clone(),
+            warn: config.warn.clone(),
+            deny: config.deny.clone(),
+            rules: vec![],
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use serde_json::Value;
+
+use crate::{
+    validation_error::{Severity, ValidationError},
+    validation_state::ValidationState,
+};
+
+/// Workflow commands which have been replaced by environment files, along with whether they
+/// still work at all, and the file that replaces them.
+const DEPRECATED_COMMANDS: &[(&str, bool, &str)] = &[
+    (
+        "set-output",
+        true,
+        "echo \"{name}={value}\" >> \"$GITHUB_OUTPUT\"",
+    ),
+    (
+        "save-state",
+        true,
+        "echo \"{name}={value}\" >> \"$GITHUB_STATE\"",
+    ),
+    (
+        "set-env",
+        false,
+        "echo \"{name}={value}\" >> \"$GITHUB_ENV\"",
+    ),
+    ("add-path", false, "echo \"{path}\" >> \"$GITHUB_PATH\""),
+];
+
+pub fn validate_workflow_commands(doc: &Value, state: &mut ValidationState) {
+    for (job_name, job) in doc["jobs"].as_object().into_iter().flatten() {
+        validate_steps(&job["steps"], &format!("/jobs/{job_name}/steps"), state);
+    }
+}
+
+pub fn validate_action_commands(doc: &Value, state: &mut ValidationState) {
+    if doc["runs"]["using"] == "composite" {
+        validate_steps(&doc["runs"]["steps"], "/runs/steps", state);
+    }
+}
+
+fn validate_steps(steps: &Value, path: &str, state: &mut ValidationState) {
+    for (i, step) in steps.as_array().into_iter().flatten().enumerate() {
+        let Some(script) = step["run"].as_str() else {
+            continue;
+        };
+
+        for (line_number, line) in script.lines().enumerate() {
+            for (command, works, replacement) in DEPRECATED_COMMANDS {
+                if !uses_command(line, command) {
+                    continue;
+                }
+
+                let status = if *works {
+                    "is deprecated, and will stop working"
+                } else {
+                    "has been disabled, and does nothing"
+                };
+                state.errors.push(ValidationError::DeprecatedCommand {
+                    code: "deprecated_workflow_command".into(),
+                    severity: Severity::Warning,
+                    path: format!("{path}/{i}/run"),
+                    title: "Deprecated workflow command".into(),
+                    detail: Some(format!(
+                        "Line {} of the script uses ::{command}, which {status}; use `{replacement}` instead",
+                        line_number + 1
+                    )),
+                });
+            }
+        }
+    }
+}
+
+/// Commands look like `::command::message` or `::command param=value::message`.
+fn uses_command(line: &str, command: &str) -> bool {
+    line.match_indices("::")
+        .filter_map(|(i, _)| line[i + 2..].strip_prefix(command))
+        .any(|rest| rest.starts_with("::") || rest.starts_with(' '))
+}
@@ -1,3 +1,6 @@
+// Test setup failures panic with `expect(&format!(...))`
+#![allow(clippy::expect_fun_call)]
+
 use fixtures::fixtures;
 use std::env::current_dir;
 use std::fs::File;
@@ -7,10 +10,14 @@ use std::process::Command;
 use std::{ffi::OsStr, fs};
 
 static REPO_DIR_WILDCARD: &str = "{{repo}}";
+static OUT_DIR_WILDCARD: &str = "{{out}}";
 
 #[derive(Debug, serde::Deserialize)]
 struct SnapshotTestConfig {
     cli_args: Option<Vec<String>>,
+    /// Files which the command writes to `{{out}}`, and which are compared with the files of
+    /// the same name in the test's directory
+    written_files: Option<Vec<String>>,
 }
 
 #[derive(Debug)]
@@ -18,6 +25,7 @@ struct SnapshotTest {
     config: SnapshotTestConfig,
     current_dir: PathBuf,
     test_dir: PathBuf,
+    out_dir: PathBuf,
 }
 
 impl SnapshotTest {
@@ -35,34 +43,50 @@ impl SnapshotTest {
             test_config_file.to_string_lossy(),
         ));
 
+        let out_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(test_dir.file_name().unwrap());
+        if out_dir.exists() {
+            fs::remove_dir_all(&out_dir).unwrap();
+        }
+        fs::create_dir_all(&out_dir).unwrap();
+
         SnapshotTest {
             config,
             current_dir: current_dir().unwrap(),
             test_dir: test_dir.to_path_buf(),
+            out_dir,
         }
     }
 
     fn build_command(&self) -> Command {
         #[cfg(not(feature = "test-js"))]
-        {
-            Command::new(assert_cmd::cargo::cargo_bin!())
-        }
+        let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!());
 
         #[cfg(feature = "test-js")]
-        {
+        let mut cmd = {
             let mut cmd = Command::new("node");
             cmd.arg("packages/cli/cli.mjs");
             cmd
+        };
+
+        if let Ok(stdin) = File::open(self.test_dir.join("stdin")) {
+            cmd.stdin(stdin);
         }
+
+        cmd
     }
 
     fn execute(self) {
         use std::ffi::OsString;
 
         let pwd = self.current_dir.to_str().unwrap();
+        let out = self.out_dir.to_str().unwrap();
+        let written_files = self.config.written_files.clone().unwrap_or_default();
 
         let cli_args: Vec<_> = if let Some(cli_args) = &self.config.cli_args {
-            cli_args.iter().map(OsString::from).collect()
+            cli_args
+                .iter()
+                .map(|arg| OsString::from(arg.replace(OUT_DIR_WILDCARD, out)))
+                .collect()
         } else {
             fs::read_dir(&self.test_dir)
                 .unwrap()
@@ -78,10 +102,12 @@ impl SnapshotTest {
 
             let stderr = fs::read_to_string(self.test_dir.join("stderr"))
                 .unwrap_or(String::from(""))
+                .replace(OUT_DIR_WILDCARD, out)
                 .replace(REPO_DIR_WILDCARD, pwd);
 
             let stdout = fs::read_to_string(self.test_dir.join("stdout"))
                 .unwrap_or(String::from(""))
+                .replace(OUT_DIR_WILDCARD, out)
                 .replace(REPO_DIR_WILDCARD, pwd);
 
             let exitcode: i32 = fs::read_to_string(self.test_dir.join("exitcode"))
@@ -99,6 +125,17 @@ impl SnapshotTest {
                 .stdout(stdout)
                 .stderr(stderr)
                 .code(exitcode);
+
+            for file in &written_files {
+                let expected = fs::read_to_string(self.test_dir.join(file))
+                    .expect(&format!("missing expected written file ({file})"))
+                    .replace(OUT_DIR_WILDCARD, out)
+                    .replace(REPO_DIR_WILDCARD, pwd);
+                let written = fs::read_to_string(self.out_dir.join(file))
+                    .expect(&format!("command did not write {file}"));
+
+                assert_eq!(written, expected, "contents of written file {file}");
+            }
         }
 
         #[cfg(feature = "test-save-snapshots")]
@@ -118,6 +155,7 @@ impl SnapshotTest {
                     .write_all(
                         String::from_utf8(result.stdout)
                             .unwrap()
+                            .replace(out, OUT_DIR_WILDCARD)
                             .replace(pwd, REPO_DIR_WILDCARD)
                             .as_bytes(),
                     )
@@ -129,6 +167,7 @@ impl SnapshotTest {
                     .write_all(
                         String::from_utf8(result.stderr)
                             .unwrap()
+                            .replace(out, OUT_DIR_WILDCARD)
                             .replace(pwd, REPO_DIR_WILDCARD)
                             .as_bytes(),
                     )
@@ -142,6 +181,17 @@ impl SnapshotTest {
                         .unwrap();
                 }
             }
+            for file in &written_files {
+                let written = fs::read_to_string(self.out_dir.join(file))
+                    .expect(&format!("command did not write {file}"));
+                fs::write(
+                    self.test_dir.join(file),
+                    written
+                        .replace(out, OUT_DIR_WILDCARD)
+                        .replace(pwd, REPO_DIR_WILDCARD),
+                )
+                .unwrap();
+            }
         }
     }
 }
@@ -154,6 +204,55 @@ impl SnapshotTest {
         reason = "The WASM implementation of action validator currently (incorrectly) accepts extended gitignore syntax"
     )
 )]
+#[cfg_attr(
+    feature = "test-js",
+    fixtures::ignore(
+        paths = "tests/fixtures/026_shellcheck",
+        reason = "The WASM implementation of action validator cannot run shellcheck"
+    )
+)]
+#[cfg_attr(
+    feature = "test-js",
+    fixtures::ignore(
+        paths = "tests/fixtures/040_language_server",
+        reason = "The WASM implementation of action validator doesn't have a language server"
+    )
+)]
+#[cfg_attr(
+    missing_schemas,
+    fixtures::ignore(
+        paths = "tests/fixtures/036_dependabot",
+        reason = "Built without the schema this test checks against"
+    )
+)]
+#[cfg_attr(
+    missing_schemas,
+    fixtures::ignore(
+        paths = "tests/fixtures/037_issue_forms",
+        reason = "Built without the schema this test checks against"
+    )
+)]
+#[cfg_attr(
+    missing_schemas,
+    fixtures::ignore(
+        paths = "tests/fixtures/038_dependabot_checks",
+        reason = "Built without the schema this test checks against"
+    )
+)]
+#[cfg_attr(
+    missing_schemas,
+    fixtures::ignore(
+        paths = "tests/fixtures/039_issue_form_checks",
+        reason = "Built without the schema this test checks against"
+    )
+)]
+#[cfg_attr(
+    missing_schemas,
+    fixtures::ignore(
+        paths = "tests/fixtures/044_issue_form_schema",
+        reason = "Built without the schema this test checks against"
+    )
+)]
 #[test]
 fn snapshot(dir: &Path) {
     SnapshotTest::new(dir).execute();